@@ -1,15 +1,13 @@
 // examples/display_mandala.rs
 
-use mandala_quicksilver::MutableMesh;
+use mandala_quicksilver::prelude::*;
 
 use quicksilver::{
-    geom::{Transform, Vector},
-    graphics::{Color, Mesh, ShapeRenderer},
+    graphics::Mesh,
     input::{ButtonState, Key},
     lifecycle::{run, Event, Settings, State, Window},
     Result,
 };
-use std::time::Instant;
 
 #[macro_use]
 extern crate log;
@@ -19,11 +17,14 @@ const CANVAS_SIZE: (f32, f32) = (1024.0, 1024.0);
 const FPS: f64 = 60.0; // Frames per second
 const UPS: f64 = 60.0; // Updates per second
 const COLOR_BACKGROUND: Color = Color::BLACK;
+const PULSE_DURATION: f64 = 1.0; // [Sec] Time to grow or shrink once
+const PULSE_MIN_SCALE: f32 = 0.5;
+const PULSE_MAX_SCALE: f32 = 4.0;
 
 fn main() {
     run::<LogoExample>(
         "Logo Demo - press ESC to exit",
-        Vector::new(CANVAS_SIZE.0, CANVAS_SIZE.1),
+        quicksilver::geom::Vector::new(CANVAS_SIZE.0, CANVAS_SIZE.1),
         Settings {
             multisampling: Some(4),
             update_rate: 1000. / UPS,
@@ -35,20 +36,17 @@ fn main() {
 
 struct LogoExample {
     filled_logo: MutableMesh,
-    start_time: Instant,
-}
-
-impl LogoExample {
-    fn seconds_since_start(&self) -> f32 {
-        self.start_time.elapsed().as_nanos() as f32 / 1000000000.0
-    }
+    pulse: Transition,
+    clock: RealTimeClock,
 }
 
 impl State for LogoExample {
     fn new() -> Result<LogoExample> {
         Ok(LogoExample {
             filled_logo: MutableMesh::new(LOGO_FILENAME),
-            start_time: Instant::now(),
+            pulse: Transition::new(0.0, PULSE_DURATION, PULSE_MIN_SCALE, PULSE_MAX_SCALE)
+                .with_easing(Easing::EaseInOut),
+            clock: RealTimeClock::new(),
         })
     }
 
@@ -63,27 +61,36 @@ impl State for LogoExample {
     }
 
     fn draw(&mut self, window: &mut Window) -> Result<()> {
-        window.clear(COLOR_BACKGROUND)?;
+        window.clear(COLOR_BACKGROUND.into())?;
 
         let mut mesh = Mesh::new();
-        let seconds_since_start = self.seconds_since_start();
-        let scale = ((seconds_since_start * 3.0).sin() as f32 + 1.0) * 2.0;
+        let seconds_since_start = self.clock.now();
+        if self.pulse.is_complete(seconds_since_start) {
+            // Turn around and pulse back the other way
+            self.pulse = Transition::new(
+                seconds_since_start,
+                PULSE_DURATION,
+                self.pulse.end_value(),
+                self.pulse.start_value(),
+            )
+            .with_easing(Easing::EaseInOut);
+        }
+        let scale = self.pulse.value(seconds_since_start);
         let color = Color {
             r: 1.0,
             g: 0.0,
-            b: (seconds_since_start * 2.5).sin(),
-            a: (seconds_since_start * 4.0).sin(),
+            b: (seconds_since_start * 2.5).sin() as f32,
+            a: (seconds_since_start * 4.0).sin() as f32,
         };
         self.filled_logo.set_color(color);
         self.filled_logo.set_transform(
             Transform::translate((CANVAS_SIZE.0 / 2.0, CANVAS_SIZE.1 / 2.0))
-                * Transform::rotate(seconds_since_start * 50.0)
+                * Transform::rotate((seconds_since_start * 50.0) as f32)
                 * Transform::scale((scale, 1.0)),
         );
-        let mut shape_renderer = ShapeRenderer::new(&mut mesh, self.filled_logo.color);
 
         // Draw the logo
-        self.filled_logo.tesselate(&mut shape_renderer);
+        self.filled_logo.tesselate(&mut mesh);
 
         // Merge the rendered mesh to screen
         window.mesh().extend(&mesh);
@@ -1,10 +1,12 @@
 // examples/display_mandala.rs
 
-use mandala_quicksilver::{Mandala, MandalaState};
+use mandala_quicksilver::{
+    Clock, Color, HotReload, Mandala, MandalaState, PetalDrawOrder, RealTimeClock, Transform,
+};
 
 use quicksilver::{
-    geom::{Transform, Vector},
-    graphics::{Color, Mesh, ShapeRenderer},
+    geom::Vector,
+    graphics::Mesh,
     input::{ButtonState, Key},
     lifecycle::{run, Event, Settings, State, Window},
     Result,
@@ -13,15 +15,6 @@ use quicksilver::{
 #[macro_use]
 extern crate log;
 
-#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
-extern crate wasm_timer;
-
-#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
-use std::time::Instant;
-
-#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
-use instant::Instant;
-
 const PETAL_FILENAME: &str = "petal.svg";
 const PETAL_COUNT: usize = 20;
 const CANVAS_SIZE: (f32, f32) = (1024.0, 1024.0);
@@ -60,19 +53,13 @@ fn main() {
 }
 
 struct MandalaExample {
-    start_time: Instant,
+    clock: RealTimeClock,
     mandala: Mandala,
-}
-
-impl MandalaExample {
-    fn seconds_since_start(&self) -> f32 {
-        self.start_time.elapsed().as_nanos() as f32 / 1000000000.0
-    }
+    hot_reload: HotReload, // Edit the petal SVG while the example runs to see the change
 }
 
 impl State for MandalaExample {
     fn new() -> Result<MandalaExample> {
-        let start_time = Instant::now();
         let mandala_state_open = MandalaState::new(
             COLOR_PETAL_OPEN,
             Transform::rotate(90),
@@ -94,18 +81,23 @@ impl State for MandalaExample {
             mandala_state_closed,
             1.0,
         );
+        mandala.set_draw_order(PetalDrawOrder::ByAngle { axis: 270.0 });
         mandala.start_transition(0.0, 3.0, 0.0);
 
+        let mut hot_reload = HotReload::new();
+        hot_reload.watch_petal(PETAL_FILENAME);
+
         Ok(MandalaExample {
-            start_time,
+            clock: RealTimeClock::new(),
             mandala,
+            hot_reload,
         })
     }
 
     fn event(&mut self, event: &Event, window: &mut Window) -> Result<()> {
         match *event {
             Event::Key(Key::Space, ButtonState::Pressed) => {
-                self.start_time = Instant::now(); // Restart the transition
+                self.clock.restart(); // Restart the transition
             }
             Event::Key(Key::Escape, ButtonState::Pressed) => {
                 window.close();
@@ -116,19 +108,22 @@ impl State for MandalaExample {
     }
 
     fn draw(&mut self, window: &mut Window) -> Result<()> {
-        window.clear(COLOR_BACKGROUND)?;
+        window.clear(COLOR_BACKGROUND.into())?;
 
         let mut mesh = Mesh::new();
-        let mut shape_renderer = ShapeRenderer::new(&mut mesh, Color::RED);
-
-        self.mandala
-            .draw(self.seconds_since_start(), &mut shape_renderer);
+        self.mandala.draw(self.clock.now(), &mut mesh);
         window.mesh().extend(&mesh);
 
         Ok(())
     }
 
     fn update(&mut self, _window: &mut Window) -> Result<()> {
+        match self.hot_reload.poll(&mut self.mandala) {
+            Ok(true) => info!("Reloaded {}", PETAL_FILENAME),
+            Ok(false) => (),
+            Err(e) => warn!("{}", e),
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,181 @@
+//! Clock-based interpolation of a single value, usable for any animated visual
+
+/// The shape of the curve a transition follows from its start value to its end value
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Start slowly and finish at full speed
+    EaseIn,
+    /// Start at full speed and slow down to finish
+    EaseOut,
+    /// Start slowly, speed up, then slow down to finish
+    EaseInOut,
+}
+
+impl Easing {
+    /// Every easing, in the order of their codes in binary session files
+    pub const ALL: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    /// The snake_case name used in files, such as `ease_in_out`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Easing::Linear => "linear",
+            Easing::EaseIn => "ease_in",
+            Easing::EaseOut => "ease_out",
+            Easing::EaseInOut => "ease_in_out",
+        }
+    }
+
+    /// The easing called 'name', as given by `name()`, or None if there is no such easing
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|e| e.name() == name)
+    }
+
+    /// Map [0.0..1.0] linear progress through time to [0.0..1.0] progress from start value to end value
+    pub fn apply(&self, percent: f32) -> f32 {
+        match *self {
+            Easing::Linear => percent,
+            Easing::EaseIn => percent * percent,
+            Easing::EaseOut => percent * (2.0 - percent),
+            Easing::EaseInOut => percent * percent * (3.0 - 2.0 * percent),
+        }
+    }
+}
+
+/// A single animation from value to value over a defined time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    start_time: f64,  // [Sec] When we started the latest transition
+    duration: f64,    // [Sec] Where we got here
+    start_value: f32, // [0.0..1.0] The position we are animating from
+    end_value: f32,   // [0.0..1.0] The position we are animating to
+    easing: Easing,
+}
+
+impl Transition {
+    /// An interplated animation from 'start_time' lasting 'duration' and sweeping from mandala state 'start_value' [0.0-1.0] to 'end_value' [0.0-1.0]
+    pub fn new(start_time: f64, duration: f64, start_value: f32, end_value: f32) -> Self {
+        Self {
+            start_time,
+            duration,
+            start_value,
+            end_value,
+            easing: Easing::default(),
+        }
+    }
+
+    /// A non-animated, fixed value
+    pub fn fixed_value(value: f32) -> Self {
+        let start_time = 0.0;
+        let duration = 0.1;
+
+        Self::new(start_time, duration, value, value)
+    }
+
+    /// The same transition following a different curve between its start and end values
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+
+        self
+    }
+
+    /// The same transition starting 'delay' [sec] later, for example after a pause
+    pub fn delayed(mut self, delay: f64) -> Self {
+        self.start_time += delay;
+
+        self
+    }
+
+    /// [Sec] When the transition started
+    pub fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    /// [Sec] How long the transition lasts
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// The value at the start of the transition
+    pub fn start_value(&self) -> f32 {
+        self.start_value
+    }
+
+    /// The value once the transition is complete
+    pub fn end_value(&self) -> f32 {
+        self.end_value
+    }
+
+    /// The curve followed from start value to end value
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// Get a [0.0..1.0] number representing %complete of the transition at 'current_time', 0.0 before it starts
+    pub fn percent(&self, current_time: f64) -> f32 {
+        let end_time = self.start_time + self.duration;
+        if current_time >= end_time {
+            return 1.0;
+        }
+
+        ((current_time - self.start_time) / self.duration).max(0.0) as f32
+    }
+
+    /// The eased value at 'current_time' [sec]
+    pub fn value(&self, current_time: f64) -> f32 {
+        let percent = self.easing.apply(self.percent(current_time));
+
+        self.start_value + (self.end_value - self.start_value) * percent
+    }
+
+    /// True once the end value has been reached
+    pub fn is_complete(&self, current_time: f64) -> bool {
+        current_time >= self.start_time + self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animation::{Easing, Transition};
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ]
+        .iter()
+        {
+            assert_eq!(0.0, easing.apply(0.0));
+            assert_eq!(1.0, easing.apply(1.0));
+        }
+        assert_eq!(0.5, Easing::EaseInOut.apply(0.5));
+    }
+
+    #[test]
+    fn test_transition_value() {
+        let transition = Transition::new(2.0, 4.0, 1.0, 3.0);
+        assert_eq!(1.0, transition.value(2.0));
+        assert_eq!(2.0, transition.value(4.0));
+        assert_eq!(3.0, transition.value(10.0));
+        assert!(!transition.is_complete(5.9));
+        assert!(transition.is_complete(6.0));
+
+        let eased = transition.with_easing(Easing::EaseIn);
+        assert_eq!(1.5, eased.value(4.0));
+    }
+}
@@ -0,0 +1,69 @@
+//! The triangles produced by drawing, in a form any renderer can consume
+
+use crate::{color::Color, geom::Vector};
+
+/// A corner of a triangle in screen coordinates with its fill color
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vertex {
+    pub position: Vector,
+    pub color: Color,
+}
+
+/// Receives each filled shape as it is drawn
+///
+/// Implement this to render mandalas with another engine. `MeshBuffer` collects everything in memory for tests and offline tools, and with the `quicksilver` feature a `quicksilver::graphics::Mesh` can be drawn into directly
+pub trait Backend {
+    /// Add one shape made of 'vertices', with 'indices' into 'vertices' listing three corners per triangle
+    fn draw_triangles(&mut self, vertices: &[Vertex], indices: &[u32]);
+}
+
+/// Plain vertex and index buffers accumulating every shape drawn into them
+#[derive(Clone, Debug, Default)]
+pub struct MeshBuffer {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of triangles drawn so far
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Empty the buffers ready to draw the next frame, keeping the allocated memory
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+}
+
+impl Backend for MeshBuffer {
+    fn draw_triangles(&mut self, vertices: &[Vertex], indices: &[u32]) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(vertices);
+        self.indices.extend(indices.iter().map(|i| offset + i));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::{Backend, MeshBuffer, Vertex};
+    use crate::{color::Color, geom::Vector};
+
+    #[test]
+    fn test_mesh_buffer_offsets_indices() {
+        let vertex = Vertex {
+            position: Vector::ZERO,
+            color: Color::RED,
+        };
+        let mut buffer = MeshBuffer::new();
+        buffer.draw_triangles(&[vertex; 3], &[0, 1, 2]);
+        buffer.draw_triangles(&[vertex; 3], &[2, 1, 0]);
+        assert_eq!(2, buffer.triangle_count());
+        assert_eq!(vec![0, 1, 2, 5, 4, 3], buffer.indices);
+    }
+}
@@ -0,0 +1,193 @@
+//! Render a recorded value timeline offline to numbered PNG frames or an animated GIF
+//!
+//! mandala-render --timeline values.csv --out frames/ [--config mandala.toml] [--petal petal.svg] [--petals 20] [--size 512] [--fps 30] [--transition 1.0]
+//!
+//! The output is an animated GIF if the '--out' name ends in '.gif', otherwise a directory of 'frame_00000.png' files. Without '--config' the mandala example is drawn, centered and scaled to fit the '--size'
+
+use mandala_quicksilver::{Color, MandalaConfig, PetalOverlap, RgbaCanvas, Timeline};
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::BufWriter,
+    path::Path,
+    process,
+    str::FromStr,
+};
+
+const USAGE: &str = "Usage: mandala-render --timeline <values.csv|values.json> --out <directory|file.gif> [--config <mandala.toml|.json|.ron>] [--petal <petal.svg>] [--petals <count>] [--size <pixels>] [--fps <frames per second>] [--transition <sec>]";
+/// The default config fills a 1024 pixel window
+const DEFAULT_CONFIG_SIZE: f32 = 1024.0;
+/// Lower is slower with fewer color banding artifacts [1..30]
+const GIF_QUANTIZE_SPEED: i32 = 10;
+
+struct Options {
+    timeline: String,
+    out: String,
+    config: MandalaConfig,
+    size: u32,
+    fps: f64,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let (mut timeline, mut out, mut config_file) = (String::new(), String::new(), None);
+        let (mut petal, mut petal_count, mut transition_duration) = (None, None, None);
+        let (mut size, mut fps) = (512, 30.0);
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            match flag.as_str() {
+                "--timeline" => timeline = value,
+                "--out" => out = value,
+                "--config" => config_file = Some(value),
+                "--petal" => petal = Some(value),
+                "--petals" => petal_count = Some(number(&flag, &value)?),
+                "--size" => size = number(&flag, &value)?,
+                "--fps" => fps = number(&flag, &value)?,
+                "--transition" => transition_duration = Some(number(&flag, &value)?),
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+        if timeline.is_empty() || out.is_empty() {
+            return Err("Both --timeline and --out are required".to_string());
+        }
+        if size == 0 || size > u16::MAX as u32 || !(fps.is_finite() && fps > 0.0) {
+            return Err("--size and --fps must be positive".to_string());
+        }
+        if transition_duration.map_or(false, |d: f64| !(d.is_finite() && d >= 0.0)) {
+            return Err("--transition must be zero or more seconds".to_string());
+        }
+
+        let mut config = match config_file {
+            Some(file_name) => MandalaConfig::load(&file_name).map_err(|e| e.to_string())?,
+            None => {
+                let default = MandalaConfig::default();
+                let fit = size as f32 / DEFAULT_CONFIG_SIZE;
+                MandalaConfig {
+                    center: (size as f32 / 2.0, size as f32 / 2.0),
+                    scale: (default.scale.0 * fit, default.scale.1 * fit),
+                    ..default
+                }
+            }
+        };
+        config.petal = petal.unwrap_or(config.petal);
+        config.petal_count = petal_count.unwrap_or(config.petal_count);
+        config.transition.duration = transition_duration.unwrap_or(config.transition.duration);
+
+        Ok(Options {
+            timeline,
+            out,
+            config,
+            size,
+            fps,
+        })
+    }
+
+    /// The solid color the petals are drawn over
+    fn background(&self) -> Color {
+        match self.config.overlap {
+            PetalOverlap::Flatten { background } => background,
+            PetalOverlap::Blend => Color::BLACK,
+        }
+    }
+}
+
+fn number<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Not a number for {}: '{}'", flag, value))
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = render(&options) {
+        eprintln!("mandala-render: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Replay the timeline one frame at a time until every transition is complete, passing each frame to the output
+fn render(options: &Options) -> Result<(), Box<dyn Error>> {
+    let timeline = Timeline::load(&options.timeline)?;
+    let mut mandala = MandalaConfig {
+        value: timeline.samples().first().map_or(0.0, |s| s.value),
+        ..options.config.clone()
+    }
+    .build()?;
+    let background = options.background();
+    let transition_duration = options.config.transition.duration;
+    let mut canvas = RgbaCanvas::new(options.size, options.size, background);
+    let frame_count =
+        ((timeline.duration() + transition_duration) * options.fps).ceil() as usize + 1;
+
+    let mut output = Output::new(options)?;
+    let mut previous_time = f64::NEG_INFINITY;
+    for frame in 0..frame_count {
+        let current_time = frame as f64 / options.fps;
+        timeline.replay(
+            &mut mandala,
+            previous_time,
+            current_time,
+            transition_duration,
+        );
+        previous_time = current_time;
+
+        canvas.clear(background);
+        mandala.draw(current_time, &mut canvas);
+        output.write_frame(frame, &canvas)?;
+    }
+
+    Ok(())
+}
+
+/// Where rendered frames go
+enum Output {
+    Frames(String),
+    Gif(gif::Encoder<BufWriter<File>>, u16),
+}
+
+impl Output {
+    fn new(options: &Options) -> Result<Self, Box<dyn Error>> {
+        if options.out.ends_with(".gif") {
+            let size = options.size as u16;
+            let mut encoder =
+                gif::Encoder::new(BufWriter::new(File::create(&options.out)?), size, size, &[])?;
+            encoder.set_repeat(gif::Repeat::Infinite)?;
+            let delay = (100.0 / options.fps).round() as u16; // GIF frame delays are in 1/100 sec
+
+            Ok(Output::Gif(encoder, delay))
+        } else {
+            fs::create_dir_all(&options.out)?;
+
+            Ok(Output::Frames(options.out.clone()))
+        }
+    }
+
+    fn write_frame(&mut self, frame: usize, canvas: &RgbaCanvas) -> Result<(), Box<dyn Error>> {
+        match self {
+            Output::Frames(directory) => {
+                canvas.save_png(Path::new(directory).join(format!("frame_{:05}.png", frame)))?
+            }
+            Output::Gif(encoder, delay) => {
+                let mut pixels = canvas.to_rgba8();
+                let mut gif_frame = gif::Frame::from_rgba_speed(
+                    canvas.width() as u16,
+                    canvas.height() as u16,
+                    &mut pixels,
+                    GIF_QUANTIZE_SPEED,
+                );
+                gif_frame.delay = *delay;
+                encoder.write_frame(&gif_frame)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+//! Learn each participant's signal range before a session, to map their raw signal onto mandala values [0.0..1.0]
+//!
+//! A `Calibration` collects samples for a while, then its `SignalRange` gives a `Normalizer`. Save the range to reuse it in later sessions with the same participant
+
+#[cfg(feature = "config")]
+use crate::config::{self, ConfigError};
+use crate::timeline::Sample;
+
+/// Summary statistics of a participant's signal, measured during calibration
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignalRange {
+    pub samples: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// Standard deviation
+    pub std: f32,
+    /// [0.0..1.0] The percentile 'low' is measured at
+    pub low_percentile: f32,
+    /// [0.0..1.0] The percentile 'high' is measured at
+    pub high_percentile: f32,
+    /// The signal at 'low_percentile', a robust minimum which ignores a few low spikes
+    pub low: f32,
+    /// The signal at 'high_percentile', a robust maximum which ignores a few high spikes
+    pub high: f32,
+}
+
+impl SignalRange {
+    /// Statistics of 'values', with 'low' and 'high' measured at the given percentiles [0.0..1.0], or None if there are no finite values
+    ///
+    /// NaN and infinite values, such as dropped sensor frames, are left out. The percentiles are limited as `Calibration::with_percentiles()` does
+    pub fn from_values(values: &[f32], low_percentile: f32, high_percentile: f32) -> Option<Self> {
+        let (low_percentile, high_percentile) = valid_percentiles(low_percentile, high_percentile);
+        let mut sorted: Vec<f32> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f32::total_cmp);
+        let count = sorted.len() as f32;
+        let mean = sorted.iter().sum::<f32>() / count;
+        let variance = sorted.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / count;
+
+        Some(Self {
+            samples: sorted.len(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean,
+            std: variance.sqrt(),
+            low_percentile,
+            high_percentile,
+            low: percentile(&sorted, low_percentile),
+            high: percentile(&sorted, high_percentile),
+        })
+    }
+
+    /// Map the range between the low and high percentiles to [0.0..1.0]
+    pub fn normalizer(&self) -> Normalizer {
+        Normalizer::new(self.low, self.high)
+    }
+
+    /// Map 'deviations' standard deviations either side of the mean to [0.0..1.0]
+    pub fn standard_normalizer(&self, deviations: f32) -> Normalizer {
+        Normalizer::new(
+            self.mean - deviations * self.std,
+            self.mean + deviations * self.std,
+        )
+    }
+
+    /// Read a range saved by `save()`, with the format chosen by the file name ending `.toml`, `.json` or `.ron`
+    #[cfg(feature = "config")]
+    pub fn load(file_name: &str) -> Result<Self, ConfigError> {
+        config::load_file(file_name)
+    }
+
+    /// Write the range for a later session, with the format chosen by the file name ending `.toml`, `.json` or `.ron`
+    #[cfg(feature = "config")]
+    pub fn save(&self, file_name: &str) -> Result<(), ConfigError> {
+        config::save_file(self, file_name)
+    }
+}
+
+/// Percentiles limited to [0.0..1.0], with NaN taken as 0.0 and 'high' no lower than 'low'
+fn valid_percentiles(low: f32, high: f32) -> (f32, f32) {
+    let low = low.max(0.0).min(1.0);
+
+    (low, high.max(low).min(1.0))
+}
+
+/// Linear interpolation between the nearest of the 'sorted' values
+fn percentile(sorted: &[f32], percentile: f32) -> f32 {
+    let position = percentile * (sorted.len() - 1) as f32;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f32)
+}
+
+/// Scales raw signal values so 'low' becomes 0.0 and 'high' becomes 1.0, clamping anything beyond
+///
+/// Give a 'low' above 'high' to invert the signal, so the mandala closes as the signal rises
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Normalizer {
+    pub low: f32,
+    pub high: f32,
+}
+
+impl Normalizer {
+    pub fn new(low: f32, high: f32) -> Self {
+        Self { low, high }
+    }
+
+    /// [0.0..1.0] Where 'value' falls between low and high. A range of zero width gives 0.0 at or below it and 1.0 above
+    ///
+    /// A NaN or infinite value, such as a dropped sensor frame, gives None
+    pub fn normalize(&self, value: f32) -> Option<f32> {
+        if !value.is_finite() {
+            return None;
+        }
+        let width = self.high - self.low;
+        if width == 0.0 {
+            return Some(if value > self.low { 1.0 } else { 0.0 });
+        }
+        let normalized = (value - self.low) / width;
+
+        normalized.is_finite().then(|| normalized.clamp(0.0, 1.0))
+    }
+
+    /// The sample with its value normalized, ready for a `SampleAdapter`, or None if the value is NaN or infinite
+    pub fn normalize_sample(&self, sample: Sample) -> Option<Sample> {
+        self.normalize(sample.value)
+            .map(|value| Sample::new(sample.time, value))
+    }
+}
+
+/// Collects samples for a fixed time to measure a participant's `SignalRange`
+#[derive(Clone, Debug)]
+pub struct Calibration {
+    duration: f64,
+    low_percentile: f32,
+    high_percentile: f32,
+    start_time: Option<f64>,
+    last_time: Option<f64>,
+    values: Vec<f32>,
+}
+
+impl Calibration {
+    /// Collect for 'duration' [sec] from the first sample, with the range between the 5th and 95th percentiles
+    ///
+    /// A negative or NaN 'duration' is taken as 0.0, which completes with the first sample
+    pub fn new(duration: f64) -> Self {
+        Self {
+            duration: duration.max(0.0),
+            low_percentile: 0.05,
+            high_percentile: 0.95,
+            start_time: None,
+            last_time: None,
+            values: Vec::new(),
+        }
+    }
+
+    /// Measure the range between these percentiles [0.0..1.0]
+    ///
+    /// They are clamped to [0.0..1.0] with NaN taken as 0.0, and a 'high' below 'low' is taken as 'low'
+    pub fn with_percentiles(mut self, low: f32, high: f32) -> Self {
+        let (low, high) = valid_percentiles(low, high);
+        self.low_percentile = low;
+        self.high_percentile = high;
+
+        self
+    }
+
+    /// [sec] How long samples are collected
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// [0.0..1.0] How much of the duration has been collected
+    pub fn progress(&self) -> f32 {
+        match (self.start_time, self.last_time) {
+            (Some(start_time), Some(last_time)) if self.duration > 0.0 => {
+                ((last_time - start_time) / self.duration).min(1.0) as f32
+            }
+            (Some(_), Some(_)) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    /// Add a sample, returning true once calibration is complete. Samples after the duration are ignored
+    pub fn push(&mut self, sample: Sample) -> bool {
+        let start_time = *self.start_time.get_or_insert(sample.time);
+        if sample.time - start_time <= self.duration {
+            self.values.push(sample.value);
+        }
+        self.last_time = Some(sample.time);
+
+        self.is_complete()
+    }
+
+    /// The range measured so far, or None before the first sample
+    pub fn range(&self) -> Option<SignalRange> {
+        SignalRange::from_values(&self.values, self.low_percentile, self.high_percentile)
+    }
+
+    /// Forget every sample to calibrate again
+    pub fn restart(&mut self) {
+        self.start_time = None;
+        self.last_time = None;
+        self.values.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calibration::{Calibration, Normalizer, SignalRange};
+    use crate::timeline::Sample;
+
+    #[test]
+    fn test_signal_range() {
+        let values: Vec<f32> = (0..=100).map(|i| i as f32).collect();
+        let range = SignalRange::from_values(&values, 0.1, 0.9).unwrap();
+        assert_eq!(101, range.samples);
+        assert_eq!((0.0, 100.0, 50.0), (range.min, range.max, range.mean));
+        assert_eq!((10.0, 90.0), (range.low, range.high));
+        assert!((range.std - 29.15).abs() < 0.01);
+        assert_eq!(None, SignalRange::from_values(&[], 0.1, 0.9));
+        assert_eq!(None, SignalRange::from_values(&[f32::NAN], 0.1, 0.9));
+
+        let with_nan =
+            SignalRange::from_values(&[1.0, f32::NAN, 3.0, f32::INFINITY], 0.0, 1.0).unwrap();
+        assert_eq!(
+            (2, 1.0, 3.0, 2.0),
+            (with_nan.samples, with_nan.low, with_nan.high, with_nan.mean)
+        );
+
+        let interpolated = SignalRange::from_values(&[0.0, 1.0], 0.25, 0.5).unwrap();
+        assert_eq!((0.25, 0.5), (interpolated.low, interpolated.high));
+    }
+
+    #[test]
+    fn test_normalizer() {
+        let normalizer = Normalizer::new(2.0, 6.0);
+        assert_eq!(Some(0.0), normalizer.normalize(-5.0));
+        assert_eq!(Some(0.25), normalizer.normalize(3.0));
+        assert_eq!(Some(1.0), normalizer.normalize(10.0));
+        assert_eq!(
+            Some(Sample::new(1.0, 0.5)),
+            normalizer.normalize_sample(Sample::new(1.0, 4.0))
+        );
+
+        let inverted = Normalizer::new(6.0, 2.0);
+        assert_eq!(Some(0.75), inverted.normalize(3.0));
+
+        let flat = Normalizer::new(1.0, 1.0);
+        assert_eq!(
+            (Some(0.0), Some(1.0)),
+            (flat.normalize(1.0), flat.normalize(1.1))
+        );
+    }
+
+    #[test]
+    fn test_normalizer_rejects_non_finite_values() {
+        let normalizer = Normalizer::new(2.0, 6.0);
+        assert_eq!(None, normalizer.normalize(f32::NAN));
+        assert_eq!(None, normalizer.normalize(f32::INFINITY));
+        assert_eq!(None, Normalizer::new(1.0, 1.0).normalize(f32::NEG_INFINITY));
+        assert_eq!(
+            None,
+            normalizer.normalize_sample(Sample::new(1.0, f32::NAN))
+        );
+    }
+
+    #[test]
+    fn test_calibration() {
+        let mut calibration = Calibration::new(2.0).with_percentiles(0.0, 1.0);
+        assert_eq!(None, calibration.range());
+        let complete: Vec<bool> = (0..6)
+            .map(|i| calibration.push(Sample::new(i as f64 * 0.5, i as f32)))
+            .collect();
+        assert_eq!(vec![false, false, false, false, true, true], complete);
+        let range = calibration.range().unwrap();
+        assert_eq!(5, range.samples); // The sample after 2.0 sec is ignored
+        assert_eq!((0.0, 4.0), (range.low, range.high));
+
+        calibration.restart();
+        assert_eq!(0.0, calibration.progress());
+        assert_eq!(None, calibration.range());
+    }
+
+    #[test]
+    fn test_invalid_percentiles_are_limited() {
+        let values = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let range = SignalRange::from_values(&values, -1.0, 2.0).unwrap();
+        assert_eq!((0.0, 4.0), (range.low, range.high));
+        let range = SignalRange::from_values(&values, 0.5, f32::NAN).unwrap();
+        assert_eq!((2.0, 2.0), (range.low, range.high));
+    }
+}
@@ -0,0 +1,256 @@
+//! Sources of the current time [sec] to animate with, as f64 so long-running installations keep sub-millisecond precision
+//!
+//! Pass `clock.now()` wherever a mandala takes 'current_time'. Real installations use a `RealTimeClock`, tests and offline rendering step a `FixedStepClock` exactly
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+/// The current time [sec]
+pub trait Clock {
+    /// [sec] Time now. This never goes backwards
+    fn now(&self) -> f64;
+}
+
+/// Wall clock time since the clock was created
+#[derive(Clone, Copy, Debug)]
+pub struct RealTimeClock {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    start: Instant,
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    start: f64, // [ms] Browser time
+}
+
+impl Default for RealTimeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RealTimeClock {
+    /// A clock reading 0.0 now
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            start: Instant::now(),
+            #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+            start: stdweb::web::Date::now(),
+        }
+    }
+
+    /// Start again from 0.0
+    pub fn restart(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Clock for RealTimeClock {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    fn now(&self) -> f64 {
+        ((stdweb::web::Date::now() - self.start) / 1000.0).max(0.0)
+    }
+}
+
+/// Simulated time which only moves when stepped, for tests and frame-by-frame rendering
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedStepClock {
+    time: f64,
+    step: f64,
+}
+
+impl FixedStepClock {
+    /// Starting at 0.0 and moving 'step' [sec] each `tick()`, for example `1.0 / 60.0` for 60 frames per second
+    ///
+    /// A negative or NaN 'step' is taken as 0.0, so time never runs backwards
+    pub fn new(step: f64) -> Self {
+        Self {
+            time: 0.0,
+            step: step.max(0.0),
+        }
+    }
+
+    /// [sec] How far each tick moves
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Move forward one step, returning the new time
+    pub fn tick(&mut self) -> f64 {
+        self.time += self.step;
+
+        self.time
+    }
+
+    /// Move forward 'seconds'. Negative or NaN 'seconds' leave the time as it is
+    pub fn advance(&mut self, seconds: f64) -> &mut Self {
+        self.time += seconds.max(0.0);
+
+        self
+    }
+}
+
+impl Clock for FixedStepClock {
+    fn now(&self) -> f64 {
+        self.time
+    }
+}
+
+/// Another clock which can be paused, so time stands still and resumes from where it stopped
+#[derive(Clone, Copy, Debug)]
+pub struct PausableClock<C: Clock> {
+    clock: C,
+    paused_at: Option<f64>, // Time on 'clock' when paused
+    paused_total: f64,      // [sec] Time spent paused before the current pause
+}
+
+impl<C: Clock> PausableClock<C> {
+    /// Running, and reading the same time as 'clock' until first paused
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            paused_at: None,
+            paused_total: 0.0,
+        }
+    }
+
+    /// True between `pause()` and `resume()`
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Stop time at its current reading. Pausing again while paused does nothing
+    pub fn pause(&mut self) -> &mut Self {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.clock.now());
+        }
+
+        self
+    }
+
+    /// Carry on from the time paused at, leaving out the time spent paused
+    pub fn resume(&mut self) -> &mut Self {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_total += self.clock.now() - paused_at;
+        }
+
+        self
+    }
+
+    /// The clock being paused, for example to step a `FixedStepClock`
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+}
+
+impl<C: Clock> Clock for PausableClock<C> {
+    fn now(&self) -> f64 {
+        self.paused_at.unwrap_or_else(|| self.clock.now()) - self.paused_total
+    }
+}
+
+/// Another clock running faster or slower, for example 0.5 for slow motion
+///
+/// Changing the scale changes the speed from that moment without a jump in time
+#[derive(Clone, Copy, Debug)]
+pub struct ScaledClock<C: Clock> {
+    clock: C,
+    scale: f64,
+    origin: f64, // Time on 'clock' when the scale last changed
+    offset: f64, // [sec] This clock's time when the scale last changed
+}
+
+impl<C: Clock> ScaledClock<C> {
+    /// Starting at the time on 'clock' and running 'scale' times as fast
+    ///
+    /// A negative or NaN 'scale' is taken as 0.0, which stops time
+    pub fn new(clock: C, scale: f64) -> Self {
+        Self {
+            origin: clock.now(),
+            offset: clock.now(),
+            clock,
+            scale: scale.max(0.0),
+        }
+    }
+
+    /// How many seconds pass on this clock for each second on the inner clock
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Run 'scale' times as fast from now on, carrying on from the current time. A negative or NaN 'scale' stops time
+    pub fn set_scale(&mut self, scale: f64) -> &mut Self {
+        self.offset = self.now();
+        self.origin = self.clock.now();
+        self.scale = scale.max(0.0);
+
+        self
+    }
+
+    /// The clock being scaled, for example to step a `FixedStepClock`
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+}
+
+impl<C: Clock> Clock for ScaledClock<C> {
+    fn now(&self) -> f64 {
+        self.offset + (self.clock.now() - self.origin) * self.scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::{Clock, FixedStepClock, PausableClock, RealTimeClock, ScaledClock};
+
+    #[test]
+    fn test_fixed_step_clock() {
+        let mut clock = FixedStepClock::new(0.25);
+        assert_eq!(0.0, clock.now());
+        assert_eq!(0.25, clock.tick());
+        clock.advance(1.0);
+        assert_eq!(1.25, clock.now());
+        clock.advance(-1.0).advance(f64::NAN);
+        assert_eq!(1.25, clock.now());
+        assert_eq!(0.0, FixedStepClock::new(-0.5).step());
+        assert_eq!(0.0, ScaledClock::new(clock, f64::NAN).scale());
+    }
+
+    #[test]
+    fn test_pausable_clock() {
+        let mut clock = PausableClock::new(FixedStepClock::new(1.0));
+        clock.inner_mut().tick();
+        clock.pause();
+        clock.inner_mut().tick();
+        assert!(clock.is_paused());
+        assert_eq!(1.0, clock.now());
+        clock.resume();
+        assert_eq!(1.0, clock.now());
+        clock.inner_mut().tick();
+        assert_eq!(2.0, clock.now());
+    }
+
+    #[test]
+    fn test_scaled_clock() {
+        let mut clock = ScaledClock::new(FixedStepClock::new(1.0), 2.0);
+        clock.inner_mut().tick();
+        assert_eq!(2.0, clock.now());
+        clock.set_scale(0.5);
+        assert_eq!(2.0, clock.now());
+        clock.inner_mut().tick();
+        assert_eq!(2.5, clock.now());
+    }
+
+    #[test]
+    fn test_real_time_clock() {
+        let mut clock = RealTimeClock::new();
+        let now = clock.now();
+        assert!((0.0..1.0).contains(&now));
+        assert!(clock.now() >= now);
+        clock.restart();
+        assert!(clock.now() < 1.0);
+    }
+}
@@ -0,0 +1,165 @@
+//! Renderer-independent colors and the blending helpers shared by the animated shapes
+
+/// An RGBA color with each channel in [0.0..1.0]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+    pub const ORANGE: Color = Color::rgb(1.0, 0.5, 0.0);
+    pub const YELLOW: Color = Color::rgb(1.0, 1.0, 0.0);
+    pub const GREEN: Color = Color::rgb(0.0, 1.0, 0.0);
+    pub const CYAN: Color = Color::rgb(0.0, 1.0, 1.0);
+    pub const BLUE: Color = Color::rgb(0.0, 0.0, 1.0);
+    pub const MAGENTA: Color = Color::rgb(1.0, 0.0, 1.0);
+    pub const PURPLE: Color = Color::rgb(0.5, 0.0, 1.0);
+
+    /// An opaque color
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    /// The same color with a different opacity
+    pub fn with_alpha(self, a: f32) -> Self {
+        Color { a, ..self }
+    }
+
+    /// Parse a CSS-style hex color, "#rrggbb" or "#rrggbbaa" with the '#' optional
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim_start_matches('#');
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .ok()
+                .map(|value| value as f32 / 255.0)
+        };
+        let a = if hex.len() == 8 { channel(3)? } else { 1.0 };
+
+        Some(Color {
+            r: channel(0)?,
+            g: channel(1)?,
+            b: channel(2)?,
+            a,
+        })
+    }
+
+    /// The color as "#rrggbb", or "#rrggbbaa" if it is not opaque
+    pub fn to_hex(&self) -> String {
+        let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let rgb = format!(
+            "#{:02x}{:02x}{:02x}",
+            byte(self.r),
+            byte(self.g),
+            byte(self.b)
+        );
+        match byte(self.a) {
+            255 => rgb,
+            a => format!("{}{:02x}", rgb, a),
+        }
+    }
+}
+
+/// Colors are written as hex strings in config files
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+
+        Color::from_hex(&hex).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "expected a color like \"#rrggbb\" or \"#rrggbbaa\", found \"{}\"",
+                hex
+            ))
+        })
+    }
+}
+
+/// Linear interpolation of each RGBA value from 'start' to 'end' at 'value' [0.0..1.0]
+///
+/// Note: this may not be aesthetically ideal as you frequently interpolate through a brighter center-of-color-wheel value on the way to your destination. Choose your colors accordingly
+pub fn interpolate_color(start: &Color, end: &Color, value: f32) -> Color {
+    Color {
+        r: start.r + (end.r - start.r) * value,
+        g: start.g + (end.g - start.g) * value,
+        b: start.b + (end.b - start.b) * value,
+        a: start.a + (end.a - start.a) * value,
+    }
+}
+
+/// The opaque color which looks the same as translucent 'color' drawn over a solid 'background'
+pub fn flatten_color(color: &Color, background: &Color) -> Color {
+    Color {
+        a: 1.0,
+        ..interpolate_color(background, color, color.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{flatten_color, interpolate_color, Color};
+
+    #[test]
+    fn test_hex_color() {
+        assert_eq!(Some(Color::RED), Color::from_hex("#ff0000"));
+        assert_eq!(
+            Some(Color::BLUE.with_alpha(0.0)),
+            Color::from_hex("0000ff00")
+        );
+        assert_eq!(None, Color::from_hex("#ff00"));
+        assert_eq!(None, Color::from_hex("#gg0000"));
+        assert_eq!("#ffff00", Color::YELLOW.to_hex());
+        assert_eq!("#00ff0080", Color::GREEN.with_alpha(0.5).to_hex());
+    }
+
+    #[test]
+    fn test_interpolate_color() {
+        let color = interpolate_color(&Color::BLACK, &Color::WHITE, 0.25);
+        assert_eq!(
+            Color {
+                r: 0.25,
+                g: 0.25,
+                b: 0.25,
+                a: 1.0
+            },
+            color
+        );
+    }
+
+    #[test]
+    fn test_flatten_color_is_opaque() {
+        let color = flatten_color(
+            &Color {
+                r: 1.0,
+                g: 0.5,
+                b: 0.0,
+                a: 0.5,
+            },
+            &Color::BLACK,
+        );
+        assert_eq!(
+            Color {
+                r: 0.5,
+                g: 0.25,
+                b: 0.0,
+                a: 1.0
+            },
+            color
+        );
+    }
+}
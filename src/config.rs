@@ -0,0 +1,449 @@
+//! Mandala settings loaded from TOML, JSON or RON files, so visuals can be tuned without recompiling
+//!
+//! Units are chosen for people editing the files: colors are "#rrggbb" or "#rrggbbaa" hex strings, rotations are degrees, positions and distances are pixels and times are seconds. Any setting left out takes the value used by the mandala example
+
+use crate::{
+    animation::Easing,
+    color::Color,
+    geom::Transform,
+    layout::{PetalDrawOrder, PetalLayout, PetalOverlap},
+    mandala::{
+        LayerKind, LayerState, Mandala, MandalaLayer, MandalaState, RangePolicy, ValueError,
+    },
+    svg::ParseError,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{error::Error, fmt, fs, io};
+
+/// Why a config could not be loaded or saved
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    /// The text could not be understood, with a description of where
+    Parse(String),
+    /// A shape the config refers to could not be loaded
+    Svg(ParseError),
+    /// The initial value is not accepted by the range policy
+    Value(ValueError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "Can not read config: {}", e),
+            ConfigError::Parse(message) => write!(f, "Can not parse config: {}", message),
+            ConfigError::Svg(e) => write!(f, "Can not load config shape: {}", e),
+            ConfigError::Value(e) => write!(f, "Can not use config value: {}", e),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<ParseError> for ConfigError {
+    fn from(e: ParseError) -> Self {
+        ConfigError::Svg(e)
+    }
+}
+
+impl From<ValueError> for ConfigError {
+    fn from(e: ValueError) -> Self {
+        ConfigError::Value(e)
+    }
+}
+
+fn parse_error(e: impl fmt::Display) -> ConfigError {
+    ConfigError::Parse(e.to_string())
+}
+
+fn format_of(file_name: &str) -> Result<ConfigFormat, ConfigError> {
+    ConfigFormat::from_file_name(file_name).ok_or_else(|| {
+        ConfigError::Parse(format!(
+            "'{}' does not end in .toml, .json or .ron",
+            file_name
+        ))
+    })
+}
+
+/// Read any settings file, with the format chosen by the file name ending `.toml`, `.json` or `.ron`
+pub(crate) fn load_file<T: DeserializeOwned>(file_name: &str) -> Result<T, ConfigError> {
+    let format = format_of(file_name)?;
+
+    from_text(&fs::read_to_string(file_name)?, format)
+}
+
+/// Write any settings file, with the format chosen by the file name ending `.toml`, `.json` or `.ron`
+pub(crate) fn save_file<T: Serialize>(value: &T, file_name: &str) -> Result<(), ConfigError> {
+    let format = format_of(file_name)?;
+    fs::write(file_name, to_text(value, format)?)?;
+
+    Ok(())
+}
+
+pub(crate) fn from_text<T: DeserializeOwned>(
+    text: &str,
+    format: ConfigFormat,
+) -> Result<T, ConfigError> {
+    match format {
+        ConfigFormat::Toml => toml::from_str(text).map_err(parse_error),
+        ConfigFormat::Json => serde_json::from_str(text).map_err(parse_error),
+        ConfigFormat::Ron => ron::de::from_str(text).map_err(parse_error),
+    }
+}
+
+pub(crate) fn to_text<T: Serialize>(
+    value: &T,
+    format: ConfigFormat,
+) -> Result<String, ConfigError> {
+    match format {
+        ConfigFormat::Toml => toml::to_string_pretty(value).map_err(parse_error),
+        ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(parse_error),
+        ConfigFormat::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+            .map_err(parse_error),
+    }
+}
+
+/// The text formats a config can be written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Ron,
+}
+
+impl ConfigFormat {
+    /// The format for a file name ending in `.toml`, `.json` or `.ron`
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        match file_name.rsplit('.').next()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "json" => Some(ConfigFormat::Json),
+            "ron" => Some(ConfigFormat::Ron),
+            _ => None,
+        }
+    }
+}
+
+/// The open or closed endpoint of petal motion, the serializable form of `MandalaState`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MandalaStateConfig {
+    pub color: Color,
+    /// [degrees] Clockwise turn of each petal around its base, applied first
+    pub rotate: f32,
+    /// [px] Move of each petal after it is turned by `rotate`. The move is then stretched by `scale`, so a petal moves `translate * scale` before the mandala scale
+    pub translate: (f32, f32),
+    /// Stretch of each petal along and across its length, applied last so it also stretches the `translate` move
+    pub scale: (f32, f32),
+}
+
+impl From<&MandalaStateConfig> for MandalaState {
+    fn from(config: &MandalaStateConfig) -> Self {
+        // The translate goes in the scale slot and the scale in the translate slot, as in the existing
+        // examples and tests, so configs draw the same petals. The petal is turned, then moved, then stretched
+        MandalaState::new(
+            config.color,
+            Transform::rotate(config.rotate),
+            Transform::translate(config.translate),
+            Transform::scale(config.scale),
+        )
+    }
+}
+
+/// The open or closed endpoint of a decorative layer, the serializable form of `LayerState`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerStateConfig {
+    pub color: Color,
+    /// [degrees]
+    #[serde(default)]
+    pub rotate: f32,
+    /// [px]
+    #[serde(default)]
+    pub translate: (f32, f32),
+    #[serde(default = "unit_scale")]
+    pub scale: (f32, f32),
+}
+
+fn unit_scale() -> (f32, f32) {
+    (1.0, 1.0)
+}
+
+impl From<&LayerStateConfig> for LayerState {
+    fn from(config: &LayerStateConfig) -> Self {
+        LayerState::new(
+            config.color,
+            Transform::translate(config.translate)
+                * Transform::rotate(config.rotate)
+                * Transform::scale(config.scale),
+        )
+    }
+}
+
+/// A decorative layer drawn with the petals
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerConfig {
+    pub kind: LayerKind,
+    /// SVG file with the shape of the layer
+    pub svg: String,
+    pub open: LayerStateConfig,
+    pub closed: LayerStateConfig,
+}
+
+impl LayerConfig {
+    pub fn build(&self) -> Result<MandalaLayer, ParseError> {
+        MandalaLayer::try_new(&self.svg, (&self.open).into(), (&self.closed).into())
+    }
+}
+
+/// How the mandala moves from value to value
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransitionConfig {
+    /// [sec] Time to reach each new value, see `Mandala::start_transition()`
+    pub duration: f64,
+    pub easing: Easing,
+    /// What is done with values outside 0.0 to 1.0
+    pub range_policy: RangePolicy,
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        Self {
+            duration: 3.0,
+            easing: Easing::default(),
+            range_policy: RangePolicy::default(),
+        }
+    }
+}
+
+/// Everything needed to create a `Mandala`, as read from or written to a config file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MandalaConfig {
+    /// SVG file with the shape of one petal
+    pub petal: String,
+    pub petal_count: usize,
+    /// [px] Screen position of the hub
+    pub center: (f32, f32),
+    /// Size of the whole mandala relative to the SVG units of the petal
+    pub scale: (f32, f32),
+    /// [0.0..1.0] Initial value from closed to open
+    pub value: f32,
+    /// [px] Distance from the hub to the base of each petal, before scaling
+    pub ring_radius: f32,
+    /// [degrees] Turn of the whole ring of petals
+    pub angular_offset: f32,
+    pub layout: PetalLayout,
+    pub draw_order: PetalDrawOrder,
+    pub overlap: PetalOverlap,
+    pub transition: TransitionConfig,
+    pub open: MandalaStateConfig,
+    pub closed: MandalaStateConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    // TOML can not write an empty array after the tables above
+    pub layers: Vec<LayerConfig>,
+}
+
+impl Default for MandalaConfig {
+    /// The crimson to translucent turquoise flower of the mandala example on a 1024 x 1024 px screen
+    fn default() -> Self {
+        Self {
+            petal: "petal.svg".to_string(),
+            petal_count: 20,
+            center: (512.0, 512.0),
+            scale: (1.8, 1.8),
+            value: 1.0,
+            ring_radius: 0.0,
+            angular_offset: 0.0,
+            layout: PetalLayout::default(),
+            draw_order: PetalDrawOrder::ByAngle { axis: 270.0 },
+            overlap: PetalOverlap::Blend,
+            transition: TransitionConfig::default(),
+            open: MandalaStateConfig {
+                color: Color {
+                    r: 220.0 / 255.0,
+                    g: 20.0 / 255.0,
+                    b: 60.0 / 255.0,
+                    a: 1.0,
+                },
+                rotate: 90.0,
+                translate: (50.0, 0.0),
+                scale: (1.0, 1.0),
+            },
+            closed: MandalaStateConfig {
+                color: Color {
+                    r: 64.0 / 255.0,
+                    g: 224.0 / 255.0,
+                    b: 208.0 / 255.0,
+                    a: 0.2,
+                },
+                rotate: 0.0,
+                translate: (0.0, 0.0),
+                scale: (0.1, 1.0),
+            },
+            layers: Vec::new(),
+        }
+    }
+}
+
+impl MandalaConfig {
+    /// Read a config file, with the format chosen by the file name ending `.toml`, `.json` or `.ron`
+    pub fn load(file_name: &str) -> Result<Self, ConfigError> {
+        load_file(file_name)
+    }
+
+    /// Write a config file, with the format chosen by the file name ending `.toml`, `.json` or `.ron`
+    pub fn save(&self, file_name: &str) -> Result<(), ConfigError> {
+        save_file(self, file_name)
+    }
+
+    /// Parse the text of a config file
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &str, format: ConfigFormat) -> Result<Self, ConfigError> {
+        from_text(text, format)
+    }
+
+    /// The text of a config file
+    pub fn to_string(&self, format: ConfigFormat) -> Result<String, ConfigError> {
+        to_text(self, format)
+    }
+
+    /// Create the mandala described by this config
+    pub fn build(&self) -> Result<Mandala, ConfigError> {
+        let mut mandala = Mandala::try_new(
+            &self.petal,
+            self.center,
+            self.scale,
+            self.petal_count,
+            (&self.open).into(),
+            (&self.closed).into(),
+            self.value,
+        )?;
+        mandala
+            .set_ring(self.ring_radius, self.angular_offset)
+            .set_draw_order(self.draw_order)
+            .set_overlap(self.overlap)
+            .set_easing(self.transition.easing)
+            .set_range_policy(self.transition.range_policy)
+            .try_set_value(self.value)?; // Checked with the configured range policy
+        mandala.set_layout(self.layout, 0.0, 0.0);
+        for layer in self.layers.iter() {
+            mandala.set_layer(layer.kind, layer.build()?);
+        }
+
+        Ok(mandala)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{ConfigError, ConfigFormat, MandalaConfig};
+    use crate::{Color, Easing, LayerKind, MeshBuffer, PetalLayout, RangePolicy, ValueError};
+
+    const TOML: &str = r##"
+        petal = "tests/petal.svg"
+        petal_count = 8
+        layout = { type = "kaleidoscope", offset = 0.25 }
+
+        [transition]
+        easing = "ease_in_out"
+        range_policy = "wrap"
+
+        [open]
+        color = "#ff000080"
+        rotate = 45
+        translate = [10, 0]
+        scale = [1, 2]
+
+        [[layers]]
+        kind = "hub"
+        svg = "tests/petal.svg"
+        open = { color = "#0000ff" }
+        closed = { color = "#00ff00", scale = [0.5, 0.5] }
+    "##;
+
+    #[test]
+    fn test_parse_human_units() {
+        let config = MandalaConfig::from_str(TOML, ConfigFormat::Toml).unwrap();
+        assert_eq!(8, config.petal_count);
+        assert_eq!(PetalLayout::Kaleidoscope { offset: 0.25 }, config.layout);
+        assert_eq!(Easing::EaseInOut, config.transition.easing);
+        assert_eq!(RangePolicy::Wrap, config.transition.range_policy);
+        assert_eq!(3.0, config.transition.duration);
+        assert_eq!(Color::from_hex("#ff000080").unwrap(), config.open.color);
+        assert_eq!(45.0, config.open.rotate);
+        assert_eq!(MandalaConfig::default().closed, config.closed);
+        assert_eq!(LayerKind::Hub, config.layers[0].kind);
+        assert_eq!((1.0, 1.0), config.layers[0].open.scale);
+    }
+
+    #[test]
+    fn test_build() {
+        let mut mandala = MandalaConfig::from_str(TOML, ConfigFormat::Toml)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(8, mandala.petal_count());
+        assert_eq!(PetalLayout::Kaleidoscope { offset: 0.25 }, mandala.layout());
+        assert!(mandala.layer(LayerKind::Hub).is_some());
+        let mut mesh = MeshBuffer::new();
+        mandala.draw(0.1, &mut mesh);
+        assert!(mesh.triangle_count() > 0);
+    }
+
+    #[test]
+    fn test_round_trip_every_format() {
+        let config = MandalaConfig::from_str(TOML, ConfigFormat::Toml).unwrap();
+        for format in [ConfigFormat::Toml, ConfigFormat::Json, ConfigFormat::Ron] {
+            let text = config.to_string(format).unwrap();
+            assert_eq!(
+                config,
+                MandalaConfig::from_str(&text, format).unwrap(),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_config_is_the_default() {
+        let config = MandalaConfig::load("static/mandala.toml").unwrap();
+        assert_eq!(MandalaConfig::default(), config);
+    }
+
+    #[test]
+    fn test_value_is_checked_with_the_range_policy() {
+        let mut config = MandalaConfig {
+            petal: "tests/petal.svg".to_string(),
+            value: 1.5,
+            ..MandalaConfig::default()
+        };
+        config.transition.range_policy = RangePolicy::Extrapolate;
+        assert_eq!(1.5, config.build().unwrap().current_value(0.0));
+
+        config.transition.range_policy = RangePolicy::Error;
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::Value(ValueError::OutOfRange(_)))
+        ));
+    }
+
+    #[test]
+    fn test_missing_svg_is_an_error() {
+        let config = MandalaConfig {
+            petal: "tests/missing.svg".to_string(),
+            ..MandalaConfig::default()
+        };
+        assert!(config.build().is_err());
+    }
+
+    #[test]
+    fn test_bad_color_is_an_error() {
+        let toml = "[open]\ncolor = \"red\"\nrotate = 0\ntranslate = [0, 0]\nscale = [1, 1]\n";
+        assert!(MandalaConfig::from_str(toml, ConfigFormat::Toml).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Renderer-independent 2D points and affine transforms
+
+use std::ops::{Add, Mul, Sub};
+
+/// A number which can be used as a coordinate or angle, so that both `(500, 500)` and `(0.5, 1.0)` work as positions
+pub trait Scalar: Copy {
+    fn float(self) -> f32;
+}
+
+macro_rules! impl_scalar {
+    ($($t:ty),*) => {
+        $(impl Scalar for $t {
+            fn float(self) -> f32 {
+                self as f32
+            }
+        })*
+    };
+}
+
+impl_scalar!(f32, f64, i8, i16, i32, u8, u16, u32);
+
+/// A 2D point or offset
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector {
+    pub const ZERO: Vector = Vector { x: 0.0, y: 0.0 };
+
+    pub fn new(x: impl Scalar, y: impl Scalar) -> Self {
+        Self {
+            x: x.float(),
+            y: y.float(),
+        }
+    }
+}
+
+impl<T: Scalar, U: Scalar> From<(T, U)> for Vector {
+    fn from((x, y): (T, U)) -> Self {
+        Vector::new(x, y)
+    }
+}
+
+/// A 3x3 affine transformation matrix, applied to a `Vector` as a column
+///
+/// Matrices can be added, subtracted and scaled element by element so that two transforms can be linearly interpolated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform([[f32; 3]; 3]);
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+
+    /// Rotate by 'angle' degrees around the origin
+    pub fn rotate(angle: impl Scalar) -> Self {
+        let (sin, cos) = angle.float().to_radians().sin_cos();
+
+        Transform([[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Move by 'offset'
+    pub fn translate(offset: impl Into<Vector>) -> Self {
+        let offset = offset.into();
+
+        Transform([[1.0, 0.0, offset.x], [0.0, 1.0, offset.y], [0.0, 0.0, 1.0]])
+    }
+
+    /// Scale by 'scale.x' horizontally and 'scale.y' vertically around the origin
+    pub fn scale(scale: impl Into<Vector>) -> Self {
+        let scale = scale.into();
+
+        Transform([[scale.x, 0.0, 0.0], [0.0, scale.y, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// A transform from its rows
+    pub fn from_array(array: [[f32; 3]; 3]) -> Self {
+        Transform(array)
+    }
+
+    /// The rows of the matrix
+    pub fn to_array(&self) -> [[f32; 3]; 3] {
+        self.0
+    }
+
+    fn map(self, other: Transform, f: impl Fn(f32, f32) -> f32) -> Transform {
+        let mut result = self.0;
+        for (row, other_row) in result.iter_mut().zip(other.0.iter()) {
+            for (value, other_value) in row.iter_mut().zip(other_row.iter()) {
+                *value = f(*value, *other_value);
+            }
+        }
+
+        Transform(result)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Mul<Transform> for Transform {
+    type Output = Transform;
+
+    fn mul(self, other: Transform) -> Transform {
+        let mut result = [[0.0; 3]; 3];
+        for (i, row) in result.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| self.0[i][k] * other.0[k][j]).sum();
+            }
+        }
+
+        Transform(result)
+    }
+}
+
+impl Mul<Vector> for Transform {
+    type Output = Vector;
+
+    fn mul(self, v: Vector) -> Vector {
+        let m = &self.0;
+
+        Vector {
+            x: m[0][0] * v.x + m[0][1] * v.y + m[0][2],
+            y: m[1][0] * v.x + m[1][1] * v.y + m[1][2],
+        }
+    }
+}
+
+impl<T: Scalar> Mul<T> for Transform {
+    type Output = Transform;
+
+    fn mul(self, scale: T) -> Transform {
+        let scale = scale.float();
+
+        self.map(self, |value, _| value * scale)
+    }
+}
+
+impl Add<Transform> for Transform {
+    type Output = Transform;
+
+    fn add(self, other: Transform) -> Transform {
+        self.map(other, |a, b| a + b)
+    }
+}
+
+impl Sub<Transform> for Transform {
+    type Output = Transform;
+
+    fn sub(self, other: Transform) -> Transform {
+        self.map(other, |a, b| a - b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::{Transform, Vector};
+
+    fn assert_near(expected: Vector, actual: Vector) {
+        assert!(
+            (expected.x - actual.x).abs() < 1e-4 && (expected.y - actual.y).abs() < 1e-4,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_transform_vector() {
+        let transform = Transform::translate((10, 20)) * Transform::scale((2, 3));
+        assert_eq!(Vector::new(12, 23), transform * Vector::new(1, 1));
+        assert_near(Vector::new(0, 1), Transform::rotate(90) * Vector::new(1, 0));
+    }
+
+    #[test]
+    fn test_interpolate_transforms() {
+        let start = Transform::translate((0, 0));
+        let end = Transform::translate((10, 0));
+        let halfway = start + (end - start) * 0.5;
+        assert_eq!(Vector::new(5, 0), halfway * Vector::ZERO);
+    }
+}
@@ -0,0 +1,316 @@
+//! Turn a stream of noisy timestamped sensor samples into smooth mandala transitions
+
+use crate::{mandala::Mandala, timeline::Sample};
+use std::collections::VecDeque;
+
+/// How much each new estimate of the sample interval and jitter counts against the estimate so far [0.0..1.0]
+const INTERVAL_WEIGHT: f64 = 0.2;
+/// Recent raw values compared against to find outliers
+const OUTLIER_WINDOW: usize = 9;
+/// Scale from median absolute deviation to standard deviation for normally distributed values
+const MAD_TO_STD: f32 = 1.4826;
+
+/// A sample of one named input signal, such as `alpha` band power from an EEG headset
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignalSample {
+    pub signal: String,
+    pub sample: Sample,
+}
+
+impl SignalSample {
+    pub fn new(signal: impl Into<String>, sample: Sample) -> Self {
+        Self {
+            signal: signal.into(),
+            sample,
+        }
+    }
+}
+
+/// How incoming values are combined with recent values to reduce noise
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Smoothing {
+    /// Use each value as it is
+    #[default]
+    None,
+    /// Mean of the last 'window' values
+    MovingAverage { window: usize },
+    /// Move 'alpha' [0.0..1.0] of the way from the previous smoothed value to each new value
+    Exponential { alpha: f32 },
+    /// Median of the last 'window' values, which ignores short spikes completely
+    Median { window: usize },
+}
+
+/// Smooths incoming samples and starts mandala transitions with a duration tuned to how regularly the samples arrive
+///
+/// Each transition lasts about one expected sample interval plus two measures of jitter, so a late sample arrives before the animation stops, but never longer than the maximum latency
+#[derive(Clone, Debug)]
+pub struct SampleAdapter {
+    smoothing: Smoothing,
+    outlier_threshold: Option<f32>,
+    min_duration: f64,
+    max_latency: f64,
+    recent: VecDeque<f32>,
+    raw: VecDeque<f32>,
+    smoothed: Option<f32>,
+    last_time: Option<f64>,
+    interval: Option<f64>,
+    jitter: f64,
+}
+
+impl Default for SampleAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SampleAdapter {
+    /// No smoothing or outlier rejection, with transitions from 0.05 to 1.0 sec
+    pub fn new() -> Self {
+        Self {
+            smoothing: Smoothing::None,
+            outlier_threshold: None,
+            min_duration: 0.05,
+            max_latency: 1.0,
+            recent: VecDeque::new(),
+            raw: VecDeque::with_capacity(OUTLIER_WINDOW),
+            smoothed: None,
+            last_time: None,
+            interval: None,
+            jitter: 0.0,
+        }
+    }
+
+    pub fn with_smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+
+        self
+    }
+
+    /// Ignore values more than 'threshold' standard deviations from the median of recent values, estimated robustly from the median absolute deviation
+    ///
+    /// Ignored values still count towards the median, so a real and lasting change in level is accepted after a few samples
+    pub fn with_outlier_rejection(mut self, threshold: f32) -> Self {
+        self.outlier_threshold = Some(threshold);
+
+        self
+    }
+
+    /// Limit transition durations to ['min_duration'..'max_latency'] [sec]
+    ///
+    /// The mandala shows each sample completely no later than 'max_latency' after it arrives
+    ///
+    /// A negative or NaN 'min_duration' is taken as 0.0, and a 'max_latency' below it as 'min_duration'
+    pub fn with_duration_limits(mut self, min_duration: f64, max_latency: f64) -> Self {
+        self.min_duration = min_duration.max(0.0);
+        self.max_latency = max_latency.max(self.min_duration);
+
+        self
+    }
+
+    /// [sec] The estimated time between samples, once two have arrived
+    pub fn interval(&self) -> Option<f64> {
+        self.interval
+    }
+
+    /// [sec] The estimated mean variation of the time between samples
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    /// [sec] How long a transition to the next value should take
+    pub fn transition_duration(&self) -> f64 {
+        match self.interval {
+            Some(interval) => {
+                (interval + 2.0 * self.jitter).clamp(self.min_duration, self.max_latency)
+            }
+            None => self.max_latency,
+        }
+    }
+
+    /// Add a sample, returning the smoothed value or None if it is rejected as an outlier
+    ///
+    /// A sample with a NaN or infinite time or value, such as a dropped sensor frame, is rejected without changing anything
+    pub fn push(&mut self, sample: Sample) -> Option<f32> {
+        if !sample.time.is_finite() || !sample.value.is_finite() {
+            return None;
+        }
+        self.update_interval(sample.time);
+        if self.is_outlier(sample.value) {
+            return None;
+        }
+
+        let smoothed = match self.smoothing {
+            Smoothing::None => sample.value,
+            Smoothing::MovingAverage { window } => {
+                self.remember(sample.value, window);
+                self.recent.iter().sum::<f32>() / self.recent.len() as f32
+            }
+            Smoothing::Exponential { alpha } => match self.smoothed {
+                Some(previous) => previous + (sample.value - previous) * alpha,
+                None => sample.value,
+            },
+            Smoothing::Median { window } => {
+                self.remember(sample.value, window);
+                median(self.recent.iter().copied().collect())
+            }
+        };
+        self.smoothed = Some(smoothed);
+
+        Some(smoothed)
+    }
+
+    /// Add a sample and, unless it is rejected, start the mandala moving to the smoothed value at 'current_time' [sec] on the render clock
+    ///
+    /// The sample time is only used to estimate the interval and jitter between samples, so it may come from the sender's own clock
+    pub fn apply(
+        &mut self,
+        sample: Sample,
+        current_time: f64,
+        mandala: &mut Mandala,
+    ) -> Option<f32> {
+        let value = self.push(sample)?;
+        mandala.start_transition(current_time, self.transition_duration(), value);
+
+        Some(value)
+    }
+
+    fn update_interval(&mut self, time: f64) {
+        if let Some(last_time) = self.last_time {
+            let interval = time - last_time;
+            if interval > 0.0 {
+                match self.interval {
+                    Some(mean) => {
+                        self.jitter += ((interval - mean).abs() - self.jitter) * INTERVAL_WEIGHT;
+                        self.interval = Some(mean + (interval - mean) * INTERVAL_WEIGHT);
+                    }
+                    None => self.interval = Some(interval),
+                }
+            }
+        }
+        self.last_time = Some(time);
+    }
+
+    fn is_outlier(&mut self, value: f32) -> bool {
+        let threshold = match self.outlier_threshold {
+            Some(threshold) => threshold,
+            None => return false,
+        };
+        let outlier = if self.raw.len() >= 3 {
+            let values: Vec<f32> = self.raw.iter().copied().collect();
+            let center = median(values.clone());
+            let deviation = median(values.iter().map(|v| (v - center).abs()).collect());
+            (value - center).abs() > threshold * (deviation * MAD_TO_STD).max(f32::EPSILON)
+        } else {
+            false
+        };
+        if self.raw.len() == OUTLIER_WINDOW {
+            self.raw.pop_front();
+        }
+        self.raw.push_back(value);
+
+        outlier
+    }
+
+    fn remember(&mut self, value: f32, window: usize) {
+        while self.recent.len() >= window.max(1) {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(value);
+    }
+}
+
+fn median(mut values: Vec<f32>) -> f32 {
+    values.sort_by(f32::total_cmp);
+    let middle = values.len() / 2;
+    if middle * 2 == values.len() {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::{SampleAdapter, Smoothing};
+    use crate::timeline::Sample;
+
+    fn push_all(adapter: &mut SampleAdapter, values: &[f32]) -> Vec<Option<f32>> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| adapter.push(Sample::new(i as f64 * 0.1, *value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_smoothing() {
+        let mut average =
+            SampleAdapter::new().with_smoothing(Smoothing::MovingAverage { window: 2 });
+        assert_eq!(
+            vec![Some(0.0), Some(0.5), Some(1.0)],
+            push_all(&mut average, &[0.0, 1.0, 1.0])
+        );
+
+        let mut exponential =
+            SampleAdapter::new().with_smoothing(Smoothing::Exponential { alpha: 0.5 });
+        assert_eq!(
+            vec![Some(0.0), Some(0.5), Some(0.75)],
+            push_all(&mut exponential, &[0.0, 1.0, 1.0])
+        );
+
+        let mut median = SampleAdapter::new().with_smoothing(Smoothing::Median { window: 3 });
+        assert_eq!(Some(0.2), push_all(&mut median, &[0.2, 0.9, 0.2])[2]);
+    }
+
+    #[test]
+    fn test_non_finite_samples_are_rejected() {
+        let mut adapter =
+            SampleAdapter::new().with_smoothing(Smoothing::Exponential { alpha: 0.5 });
+        let pushed = push_all(&mut adapter, &[0.0, f32::NAN, 1.0, f32::INFINITY, 1.0]);
+        assert_eq!(vec![Some(0.0), None, Some(0.5), None, Some(0.75)], pushed);
+        assert_eq!(None, adapter.push(Sample::new(f64::NAN, 0.5)));
+
+        let mut median = SampleAdapter::new().with_smoothing(Smoothing::Median { window: 3 });
+        assert_eq!(
+            Some(0.2),
+            push_all(&mut median, &[0.2, f32::NAN, 0.9, 0.2])[3]
+        );
+    }
+
+    #[test]
+    fn test_outlier_rejection() {
+        let mut adapter = SampleAdapter::new().with_outlier_rejection(3.0);
+        let values = [0.50, 0.52, 0.48, 0.51, 5.0, 0.49];
+        let pushed = push_all(&mut adapter, &values);
+        assert_eq!(None, pushed[4]);
+        assert_eq!(Some(0.49), pushed[5]);
+
+        // A lasting change is accepted once it is most of the recent values
+        let pushed = push_all(&mut adapter, &[0.9; 8]);
+        assert_eq!(Some(0.9), *pushed.last().unwrap());
+    }
+
+    #[test]
+    fn test_transition_duration_follows_interval_and_jitter() {
+        let mut adapter = SampleAdapter::new();
+        assert_eq!(1.0, adapter.transition_duration()); // Max latency until the interval is known
+
+        let mut time = 0.0;
+        for i in 0..50 {
+            time += if i % 2 == 0 { 0.15 } else { 0.25 };
+            adapter.push(Sample::new(time, 0.5));
+        }
+        let interval = adapter.interval().unwrap();
+        assert!((interval - 0.2).abs() < 0.02, "{}", interval);
+        assert!(
+            (adapter.jitter() - 0.05).abs() < 0.02,
+            "{}",
+            adapter.jitter()
+        );
+        assert!((adapter.transition_duration() - 0.3).abs() < 0.05);
+
+        let mut limited = SampleAdapter::new().with_duration_limits(0.05, 0.1);
+        push_all(&mut limited, &[0.0; 3]);
+        assert_eq!(0.1, limited.transition_duration());
+    }
+}
@@ -0,0 +1,235 @@
+//! How copies of the petal are arranged around the hub and layered where they overlap
+
+use crate::color::flatten_color;
+use crate::color::Color;
+
+/// How copies of the petal are arranged around the hub
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum PetalLayout {
+    /// Petals evenly spaced by rotation alone
+    #[default]
+    Rotational,
+    /// Petals evenly spaced with every other petal mirrored across its own axis
+    Dihedral,
+    /// The circle is cut into one wedge per petal, the petal sits 'offset' [0.0..1.0] of the way across the first wedge, and each wedge is the mirror image of its neighbours
+    ///
+    /// With an odd petal count the first and last wedges are not mirror images of each other
+    Kaleidoscope { offset: f32 },
+    /// Sunflower-like phyllotaxis with each petal turned by the golden angle from the one before and pushed 'spacing * sqrt(index)' further out from the hub
+    Spiral { spacing: f32 },
+}
+
+/// The golden angle [degrees] between successive petals of a `PetalLayout::Spiral`
+pub const GOLDEN_ANGLE: f32 = 137.507_77;
+
+impl PetalLayout {
+    /// Where petal 'index' of 'petal_count' is placed by this layout
+    pub(crate) fn placement(&self, index: usize, petal_count: usize) -> PetalPlacement {
+        let wedge = 360.0 / petal_count as f32;
+        let mirrored = index % 2 == 1;
+        let (angle, radius, mirror) = match *self {
+            PetalLayout::Rotational => (wedge * index as f32, 0.0, false),
+            PetalLayout::Dihedral => (wedge * index as f32, 0.0, mirrored),
+            PetalLayout::Kaleidoscope { offset } => {
+                if mirrored {
+                    (wedge * (index as f32 + 1.0 - offset), 0.0, true)
+                } else {
+                    (wedge * (index as f32 + offset), 0.0, false)
+                }
+            }
+            PetalLayout::Spiral { spacing } => (
+                (GOLDEN_ANGLE * index as f32) % 360.0,
+                spacing * (index as f32).sqrt(),
+                false,
+            ),
+        };
+
+        PetalPlacement {
+            angle,
+            radius,
+            mirror: if mirror { -1.0 } else { 1.0 },
+        }
+    }
+}
+
+/// The order petals are drawn in, which decides which petal is on top where neighbours overlap
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum PetalDrawOrder {
+    /// First petal first, so each petal is on top of the one before and the last petal is on top of the first
+    #[default]
+    Index,
+    /// Last petal first, the mirror image of `Index`
+    Reverse,
+    /// Even petals first then odd petals, so every other petal is on top of both its neighbours
+    Alternating,
+    /// Petals furthest from the 'axis' angle [degrees] first, so overlaps either side of that axis are mirror images
+    ByAngle { axis: f32 },
+}
+
+impl PetalDrawOrder {
+    /// The indexes of petals at 'angles' [degrees] in the order they should be drawn
+    pub(crate) fn sort(&self, angles: &[f32]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..angles.len()).collect();
+        match *self {
+            PetalDrawOrder::Index => (),
+            PetalDrawOrder::Reverse => order.reverse(),
+            PetalDrawOrder::Alternating => order.sort_by_key(|i| i % 2),
+            PetalDrawOrder::ByAngle { axis } => {
+                let distance = |i: &usize| {
+                    let d = (angles[*i] - axis).rem_euclid(360.0);
+                    d.min(360.0 - d)
+                };
+                order.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+            }
+        }
+
+        order
+    }
+}
+
+/// How translucent petals combine where they overlap
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum PetalOverlap {
+    /// Normal alpha blending, so overlapping areas are more opaque than a single petal
+    #[default]
+    Blend,
+    /// Pre-mix each translucent petal color with a solid 'background' color and draw it opaque, so overlapping areas look the same as a single petal
+    ///
+    /// This is only correct when the petals are drawn directly over that solid color. The colors are mixed without knowing about any `LayerKind::Background` layer or other shape under the petals, so over those they are wrong
+    Flatten { background: Color },
+}
+
+impl PetalOverlap {
+    /// The color to draw a petal with
+    pub(crate) fn apply(&self, color: Color) -> Color {
+        match *self {
+            PetalOverlap::Blend => color,
+            PetalOverlap::Flatten { background } => flatten_color(&color, &background),
+        }
+    }
+}
+
+/// The position of a single petal relative to the ring
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct PetalPlacement {
+    pub(crate) angle: f32,  // [Degrees] Rotation around the hub
+    pub(crate) radius: f32, // Extra distance out from the ring radius
+    pub(crate) mirror: f32, // 1.0 is drawn as is, -1.0 is mirrored across the petal's own axis
+}
+
+impl PetalPlacement {
+    /// Linear interpolation of each value at [0.0..1.0] 'percent' of the way to 'end'
+    pub(crate) fn interpolate(&self, end: &PetalPlacement, percent: f32) -> PetalPlacement {
+        PetalPlacement {
+            angle: self.angle + (end.angle - self.angle) * percent,
+            radius: self.radius + (end.radius - self.radius) * percent,
+            mirror: self.mirror + (end.mirror - self.mirror) * percent,
+        }
+    }
+
+    /// The same placement with the angle moved by whole turns so that animating from 'start' takes the short way around
+    pub(crate) fn nearest_to(&self, start: &PetalPlacement) -> PetalPlacement {
+        let turn = ((self.angle - start.angle) / 360.0).round() * 360.0;
+
+        PetalPlacement {
+            angle: self.angle - turn,
+            ..*self
+        }
+    }
+}
+
+/// The placement and visibility a single petal is animating between while the petal count or layout changes
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PetalSlot {
+    pub(crate) start: PetalPlacement, // Where the petal was when the latest relayout started
+    pub(crate) end: PetalPlacement,   // Where the petal will be when relayout completes
+    pub(crate) start_presence: f32,   // [0.0..1.0] Petal scale and opacity, 0.0 is not drawn
+    pub(crate) end_presence: f32,     // [0.0..1.0] Petal scale and opacity when relayout completes
+}
+
+impl PetalSlot {
+    /// A fully visible petal which is not moving
+    pub(crate) fn fixed(placement: PetalPlacement) -> Self {
+        Self {
+            start: placement,
+            end: placement,
+            start_presence: 1.0,
+            end_presence: 1.0,
+        }
+    }
+
+    /// Linear interpolation of the placement at [0.0..1.0] 'percent' of the relayout
+    pub(crate) fn placement(&self, percent: f32) -> PetalPlacement {
+        self.start.interpolate(&self.end, percent)
+    }
+
+    /// Linear interpolation of the presence at [0.0..1.0] 'percent' of the relayout
+    pub(crate) fn presence(&self, percent: f32) -> f32 {
+        self.start_presence + (self.end_presence - self.start_presence) * percent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::{PetalDrawOrder, PetalLayout, PetalPlacement, GOLDEN_ANGLE};
+
+    #[test]
+    fn test_kaleidoscope_layout_mirrors_alternate_wedges() {
+        let layout = PetalLayout::Kaleidoscope { offset: 0.25 };
+        let first = layout.placement(0, 4);
+        let second = layout.placement(1, 4);
+        assert_eq!(22.5, first.angle);
+        assert_eq!(1.0, first.mirror);
+        assert_eq!(157.5, second.angle);
+        assert_eq!(-1.0, second.mirror);
+    }
+
+    #[test]
+    fn test_spiral_layout() {
+        let layout = PetalLayout::Spiral { spacing: 10.0 };
+        let placement = layout.placement(4, 100);
+        assert_eq!((GOLDEN_ANGLE * 4.0) % 360.0, placement.angle);
+        assert_eq!(20.0, placement.radius);
+    }
+
+    #[test]
+    fn test_relayout_takes_the_short_way_around() {
+        let start = PetalPlacement {
+            angle: 350.0,
+            radius: 0.0,
+            mirror: 1.0,
+        };
+        let end = PetalPlacement {
+            angle: 10.0,
+            ..start
+        };
+        assert_eq!(370.0, end.nearest_to(&start).angle);
+    }
+
+    #[test]
+    fn test_petal_draw_order() {
+        let angles = [0.0, 90.0, 180.0, 270.0];
+        assert_eq!(vec![0, 1, 2, 3], PetalDrawOrder::Index.sort(&angles));
+        assert_eq!(vec![3, 2, 1, 0], PetalDrawOrder::Reverse.sort(&angles));
+        assert_eq!(vec![0, 2, 1, 3], PetalDrawOrder::Alternating.sort(&angles));
+        assert_eq!(
+            vec![0, 1, 3, 2],
+            PetalDrawOrder::ByAngle { axis: 180.0 }.sort(&angles)
+        );
+    }
+}
@@ -0,0 +1,1093 @@
+//! A single ring of petals around a hub, animated between open and closed states
+
+use crate::{
+    animation::{Easing, Transition},
+    backend::Backend,
+    clock::{Clock, RealTimeClock},
+    color::{interpolate_color, Color},
+    geom::{Transform, Vector},
+    input::SignalSample,
+    layout::{PetalDrawOrder, PetalLayout, PetalOverlap, PetalSlot},
+    mesh::MutableMesh,
+    observer::{FrameStats, MandalaObserver},
+    session::{Session, SessionEvent},
+    svg::{svg_document, ParseError},
+};
+use log::{debug, trace};
+use std::{error::Error, fmt};
+
+/// What a mandala does with values outside [0.0..1.0], from closed to open
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum RangePolicy {
+    /// Stop at fully closed or fully open
+    #[default]
+    Clamp,
+    /// Carry the petal transforms on past the closed and open states, for exaggerated motion
+    Extrapolate,
+    /// Wrap around, so 1.25 is drawn as 0.25, for cyclic signals such as phase
+    Wrap,
+    /// Reject values outside the range with `ValueError::OutOfRange`, leaving the mandala as it was
+    Error,
+}
+
+impl RangePolicy {
+    /// The value to accept for a new target, or why it is rejected
+    fn accept(self, value: f32) -> Result<f32, ValueError> {
+        if !value.is_finite() {
+            return Err(ValueError::NotFinite(value));
+        }
+        match self {
+            RangePolicy::Clamp => Ok(value.clamp(0.0, 1.0)),
+            RangePolicy::Error if !(0.0..=1.0).contains(&value) => {
+                Err(ValueError::OutOfRange(value))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// The value to draw
+    fn apply(self, value: f32) -> f32 {
+        match self {
+            RangePolicy::Extrapolate => value,
+            RangePolicy::Wrap if !(0.0..=1.0).contains(&value) => value.rem_euclid(1.0),
+            _ => value.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Why a value or transition was not accepted by a mandala
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueError {
+    /// The value is NaN or infinite
+    NotFinite(f32),
+    /// The value is outside [0.0..1.0] with `RangePolicy::Error`
+    OutOfRange(f32),
+    /// The transition duration is negative or NaN
+    InvalidDuration(f64),
+    /// The mandala is frozen with `Mandala::freeze_at()` and does not accept new values until unfrozen
+    Frozen,
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueError::NotFinite(value) => write!(f, "Mandala value {} is not a number", value),
+            ValueError::OutOfRange(value) => {
+                write!(f, "Mandala value {} is outside 0.0 to 1.0", value)
+            }
+            ValueError::InvalidDuration(duration) => {
+                write!(f, "Transition duration {} is not a time", duration)
+            }
+            ValueError::Frozen => write!(f, "Mandala is frozen"),
+        }
+    }
+}
+
+impl Error for ValueError {}
+
+/// A representation of how open/closed the mandala is to mark the endpoints of motion
+#[derive(Debug)]
+pub struct MandalaState {
+    color: Color,
+    petal_rotate_transform: Transform,
+    petal_scale_transform: Transform,
+    petal_translate_transform: Transform,
+}
+
+impl MandalaState {
+    /// Create a new open or closed state for the manipulation of petals
+    pub fn new(
+        color: Color,
+        petal_rotate_transform: Transform,
+        petal_scale_transform: Transform,
+        petal_translate_transform: Transform,
+    ) -> Self {
+        Self {
+            color,
+            petal_rotate_transform,
+            petal_scale_transform,
+            petal_translate_transform,
+        }
+    }
+}
+
+/// Where a decorative layer is drawn relative to the ring of petals
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum LayerKind {
+    /// Drawn first, behind the petals, for example a halo
+    Background,
+    /// Drawn over the base of the petals, for example a center disc
+    Hub,
+    /// Drawn last, over everything else
+    Foreground,
+}
+
+/// The open or closed endpoint of the motion of a decorative layer
+#[derive(Debug)]
+pub struct LayerState {
+    color: Color,
+    transform: Transform,
+}
+
+impl LayerState {
+    /// Create a new open or closed state for a layer, with 'transform' applied relative to the mandala center
+    pub fn new(color: Color, transform: Transform) -> Self {
+        Self { color, transform }
+    }
+}
+
+/// A decorative SVG shape which moves with the mandala center and animates with the same value as the petals
+pub struct MandalaLayer {
+    mesh: MutableMesh,
+    layer_state_open: LayerState,
+    layer_state_closed: LayerState,
+}
+
+impl MandalaLayer {
+    /// Create a layer from an SVG file and its fully open and fully closed states, panicking if the SVG can not be loaded
+    pub fn new(
+        svg_file_name: &str,
+        layer_state_open: LayerState,
+        layer_state_closed: LayerState,
+    ) -> Self {
+        Self::try_new(svg_file_name, layer_state_open, layer_state_closed)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a layer from an SVG file and its fully open and fully closed states
+    pub fn try_new(
+        svg_file_name: &str,
+        layer_state_open: LayerState,
+        layer_state_closed: LayerState,
+    ) -> Result<Self, ParseError> {
+        Ok(Self::with_mesh(
+            MutableMesh::load(svg_file_name)?,
+            layer_state_open,
+            layer_state_closed,
+        ))
+    }
+
+    /// Create a layer from a shape which has already been loaded
+    pub fn with_mesh(
+        mesh: MutableMesh,
+        layer_state_open: LayerState,
+        layer_state_closed: LayerState,
+    ) -> Self {
+        Self {
+            mesh,
+            layer_state_open,
+            layer_state_closed,
+        }
+    }
+
+    /// Replace the shape of the layer from an SVG file, for example after it has been edited
+    pub fn reload(&mut self, svg_file_name: &str) -> Result<(), ParseError> {
+        self.mesh.reload(svg_file_name)
+    }
+
+    /// Move and recolor the layer to 'value' [0.0..1.0] open, relative to 'mandala_center', ready to render
+    fn update(&mut self, mandala_center: Transform, value: f32) -> &mut MutableMesh {
+        let closed = &self.layer_state_closed;
+        let open = &self.layer_state_open;
+        let transform = closed.transform + (open.transform - closed.transform) * value;
+
+        self.mesh
+            .set_color(interpolate_color(
+                &closed.color,
+                &open.color,
+                value.clamp(0.0, 1.0),
+            ))
+            .set_transform(mandala_center * transform)
+    }
+}
+
+/// What a running mandala carries over when it is rebuilt
+#[cfg(feature = "config")]
+pub(crate) struct RuntimeState {
+    transition: Transition,
+    paused_at: Option<f64>,
+    frozen: bool,
+    recording: Option<Session>,
+    observer: Option<Box<dyn MandalaObserver + Send>>,
+}
+
+/// A flower-like set of "petals" arranged evenly around a central hub
+///
+/// The petals can "open", change color and other tranformations applied at runtime with clock-based smoothing between rendered frames. The hub is invisible unless a `LayerKind::Hub` layer is set, and optional background and foreground layers can be added in the same way
+pub struct Mandala {
+    petal_count: usize,
+    mandala_state_open: MandalaState,
+    mandala_state_closed: MandalaState,
+    mandala_center: Transform,
+    layout: PetalLayout,
+    draw_order: PetalDrawOrder,
+    overlap: PetalOverlap,
+    petal_slots: Vec<PetalSlot>,
+    petal_count_transition: Transition,
+    ring_radius: f32,
+    angular_offset: f32,
+    petal: MutableMesh,
+    easing: Easing,
+    range_policy: RangePolicy,
+    background: Option<MandalaLayer>,
+    hub: Option<MandalaLayer>,
+    foreground: Option<MandalaLayer>,
+    current_transition: Transition,
+    paused_at: Option<f64>, // [sec] When animation stopped, until resumed
+    frozen: bool,
+    recording: Option<Session>,
+    observer: Option<Box<dyn MandalaObserver + Send>>,
+}
+
+impl Mandala {
+    /// Create a new Mandala
+    ///
+    /// By default, this will render a 3sec transition from open to closed state on creation. You can tailor this by
+    ///
+    /// Panics if the petal SVG can not be loaded
+    pub fn new(
+        petal_svg_filename: &str,
+        screen_position: impl Into<Vector>,
+        scale: impl Into<Vector>,
+        petal_count: usize,
+        mandala_state_open: MandalaState,
+        mandala_state_closed: MandalaState,
+        value: f32,
+    ) -> Self {
+        Self::try_new(
+            petal_svg_filename,
+            screen_position,
+            scale,
+            petal_count,
+            mandala_state_open,
+            mandala_state_closed,
+            value,
+        )
+        .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a new Mandala, or report why the petal SVG can not be loaded
+    pub fn try_new(
+        petal_svg_filename: &str,
+        screen_position: impl Into<Vector>,
+        scale: impl Into<Vector>,
+        petal_count: usize,
+        mandala_state_open: MandalaState,
+        mandala_state_closed: MandalaState,
+        value: f32,
+    ) -> Result<Self, ParseError> {
+        Ok(Self::with_petal(
+            MutableMesh::load(petal_svg_filename)?,
+            screen_position,
+            scale,
+            petal_count,
+            mandala_state_open,
+            mandala_state_closed,
+            value,
+        ))
+    }
+
+    /// Create a new Mandala from a petal shape which has already been loaded
+    ///
+    /// A 'value' outside [0.0..1.0] is kept, and drawn as the range policy set later says. A NaN or infinite 'value' is logged and the mandala starts closed at 0.0
+    pub fn with_petal(
+        petal: MutableMesh,
+        screen_position: impl Into<Vector>,
+        scale: impl Into<Vector>,
+        petal_count: usize,
+        mandala_state_open: MandalaState,
+        mandala_state_closed: MandalaState,
+        value: f32,
+    ) -> Self {
+        let mandala_center = Transform::translate(screen_position) * Transform::scale(scale);
+        let layout = PetalLayout::default();
+        let petal_slots = (0..petal_count)
+            .map(|i| PetalSlot::fixed(layout.placement(i, petal_count)))
+            .collect();
+        let value = if value.is_finite() {
+            value
+        } else {
+            debug!("{}, starting closed", ValueError::NotFinite(value));
+            0.0
+        };
+        let current_transition = Transition::fixed_value(value);
+
+        Self {
+            petal_count,
+            mandala_state_open,
+            mandala_state_closed,
+            mandala_center,
+            layout,
+            draw_order: PetalDrawOrder::default(),
+            overlap: PetalOverlap::default(),
+            petal_slots,
+            petal_count_transition: Transition::fixed_value(1.0),
+            ring_radius: 0.0,
+            angular_offset: 0.0,
+            current_transition,
+            petal,
+            easing: Easing::default(),
+            range_policy: RangePolicy::default(),
+            background: None,
+            hub: None,
+            foreground: None,
+            paused_at: None,
+            frozen: false,
+            recording: None,
+            observer: None,
+        }
+    }
+
+    /// Replace the petal shape from an SVG file, for example after it has been edited, keeping the animation as it is
+    ///
+    /// If the file can not be loaded the petal is unchanged
+    pub fn reload_petal(&mut self, petal_svg_filename: &str) -> Result<(), ParseError> {
+        self.petal.reload(petal_svg_filename)
+    }
+
+    /// Add a decorative layer, replacing any previous layer of the same kind
+    ///
+    /// Layers are drawn in the order background, petals, hub, foreground
+    pub fn set_layer(&mut self, kind: LayerKind, layer: MandalaLayer) -> &mut Self {
+        *self.layer_slot(kind) = Some(layer);
+
+        self
+    }
+
+    /// Take away a decorative layer, returning it if there was one
+    pub fn remove_layer(&mut self, kind: LayerKind) -> Option<MandalaLayer> {
+        self.layer_slot(kind).take()
+    }
+
+    /// The decorative layer of this kind, if any
+    pub fn layer(&self, kind: LayerKind) -> Option<&MandalaLayer> {
+        match kind {
+            LayerKind::Background => self.background.as_ref(),
+            LayerKind::Hub => self.hub.as_ref(),
+            LayerKind::Foreground => self.foreground.as_ref(),
+        }
+    }
+
+    /// The decorative layer of this kind, if any, to change
+    pub fn layer_mut(&mut self, kind: LayerKind) -> Option<&mut MandalaLayer> {
+        self.layer_slot(kind).as_mut()
+    }
+
+    fn layer_slot(&mut self, kind: LayerKind) -> &mut Option<MandalaLayer> {
+        match kind {
+            LayerKind::Background => &mut self.background,
+            LayerKind::Hub => &mut self.hub,
+            LayerKind::Foreground => &mut self.foreground,
+        }
+    }
+
+    /// Move the hub of the mandala to a new screen position and scale
+    pub fn set_center(
+        &mut self,
+        screen_position: impl Into<Vector>,
+        scale: impl Into<Vector>,
+    ) -> &mut Self {
+        self.mandala_center = Transform::translate(screen_position) * Transform::scale(scale);
+
+        self
+    }
+
+    /// Push every petal 'radius' units out from the hub and turn the whole ring by 'angular_offset' degrees
+    ///
+    /// This is applied before the open/closed state transforms, so a radius of 0.0 and offset of 0.0 (the defaults) draw exactly as before
+    pub fn set_ring(&mut self, radius: f32, angular_offset: f32) -> &mut Self {
+        self.ring_radius = radius;
+        self.angular_offset = angular_offset;
+
+        self
+    }
+
+    /// The number of petals the mandala has, or will have once any petal count change completes
+    pub fn petal_count(&self) -> usize {
+        self.petal_count
+    }
+
+    /// Animate from the current number of petals to 'petal_count' over 'transition_duration' [sec] starting at 'current_time' [sec]
+    ///
+    /// Remaining petals slide to their new positions in the layout, added petals grow and fade in at their final position and removed petals shrink and fade out where they are. Calling this again before a previous change completes starts smoothly from the current in-between layout
+    pub fn set_petal_count(
+        &mut self,
+        petal_count: usize,
+        current_time: f64,
+        transition_duration: f64,
+    ) {
+        self.relayout(self.layout, petal_count, current_time, transition_duration);
+    }
+
+    /// The arrangement of petals around the hub, or the arrangement they are moving to if a layout change is in progress
+    pub fn layout(&self) -> PetalLayout {
+        self.layout
+    }
+
+    /// Animate every petal from where it is at 'current_time' [sec] to its place in the new 'layout' over 'transition_duration' [sec]
+    ///
+    /// Petals which change between mirrored and not mirrored flatten along their axis and open out again the other way round
+    pub fn set_layout(&mut self, layout: PetalLayout, current_time: f64, transition_duration: f64) {
+        self.relayout(layout, self.petal_count, current_time, transition_duration);
+    }
+
+    /// The order petals are drawn in, which decides which is on top where they overlap
+    pub fn draw_order(&self) -> PetalDrawOrder {
+        self.draw_order
+    }
+
+    /// Choose which petals are drawn on top where they overlap
+    pub fn set_draw_order(&mut self, draw_order: PetalDrawOrder) -> &mut Self {
+        self.draw_order = draw_order;
+
+        self
+    }
+
+    /// How translucent petals combine where they overlap
+    pub fn overlap(&self) -> PetalOverlap {
+        self.overlap
+    }
+
+    /// Choose how translucent petals combine where they overlap
+    pub fn set_overlap(&mut self, overlap: PetalOverlap) -> &mut Self {
+        self.overlap = overlap;
+
+        self
+    }
+
+    /// Start animating from the current in-between petal placement to 'petal_count' petals arranged by 'layout'
+    fn relayout(
+        &mut self,
+        layout: PetalLayout,
+        petal_count: usize,
+        current_time: f64,
+        transition_duration: f64,
+    ) {
+        let current_time = self.animation_time(current_time);
+        let transition_duration = transition_duration.max(0.0);
+        let percent = self.petal_count_transition.percent(current_time);
+        let (remaining, removed): (Vec<PetalSlot>, Vec<PetalSlot>) = self
+            .petal_slots
+            .iter()
+            .map(|slot| PetalSlot {
+                start: slot.placement(percent),
+                end: slot.end,
+                start_presence: slot.presence(percent),
+                end_presence: slot.end_presence,
+            })
+            .filter(|slot| slot.start_presence > 0.0 || slot.end_presence > 0.0)
+            .partition(|slot| slot.end_presence > 0.0);
+
+        let mut petal_slots = Vec::with_capacity(remaining.len().max(petal_count) + removed.len());
+        for (i, slot) in remaining.iter().enumerate() {
+            if i < petal_count {
+                petal_slots.push(PetalSlot {
+                    end: layout.placement(i, petal_count).nearest_to(&slot.start),
+                    end_presence: 1.0,
+                    ..*slot
+                });
+            } else {
+                petal_slots.push(PetalSlot {
+                    end: slot.start,
+                    end_presence: 0.0,
+                    ..*slot
+                });
+            }
+        }
+        for i in remaining.len()..petal_count {
+            let placement = layout.placement(i, petal_count);
+            petal_slots.push(PetalSlot {
+                start_presence: 0.0,
+                ..PetalSlot::fixed(placement)
+            });
+        }
+        petal_slots.extend(removed.iter().map(|slot| PetalSlot {
+            end: slot.start,
+            ..*slot
+        }));
+
+        self.layout = layout;
+        self.petal_count = petal_count;
+        self.petal_slots = petal_slots;
+        self.petal_count_transition = Transition::new(current_time, transition_duration, 0.0, 1.0);
+    }
+
+    /// Distance from the hub to the base of each petal before state transforms are applied
+    pub fn ring_radius(&self) -> f32 {
+        self.ring_radius
+    }
+
+    /// Rotation [degrees] of the first petal away from the default position
+    pub fn angular_offset(&self) -> f32 {
+        self.angular_offset
+    }
+
+    /// Jump immediately to 'value' [0.0-1.0] with no animation
+    ///
+    /// A value the range policy does not accept is ignored, use `try_set_value()` to find out why
+    pub fn set_value(&mut self, value: f32) -> &mut Self {
+        if let Err(e) = self.try_set_value(value) {
+            debug!("{}, value not set", e);
+        }
+
+        self
+    }
+
+    /// Jump immediately to 'value' [0.0-1.0] with no animation, or report why the value is not accepted
+    pub fn try_set_value(&mut self, value: f32) -> Result<(), ValueError> {
+        if self.frozen {
+            return Err(ValueError::Frozen);
+        }
+        self.current_transition = Transition::fixed_value(self.range_policy.accept(value)?);
+
+        Ok(())
+    }
+
+    /// What is done with values outside [0.0..1.0], by default `RangePolicy::Clamp`
+    pub fn range_policy(&self) -> RangePolicy {
+        self.range_policy
+    }
+
+    /// Choose what is done with values outside [0.0..1.0]. Colors are always kept within the closed and open colors
+    pub fn set_range_policy(&mut self, range_policy: RangePolicy) -> &mut Self {
+        self.range_policy = range_policy;
+
+        self
+    }
+
+    /// The curve followed by transitions started from now on
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// The curve followed by transitions started after this, for example `Easing::EaseInOut` to soften the start and end of each slide
+    pub fn set_easing(&mut self, easing: Easing) -> &mut Self {
+        self.easing = easing;
+
+        self
+    }
+
+    /// Initiate an animated transition from the value at 'current_time' [sec] value to 'target_value' [0.0-1.0] which will complete 'transition_duration' [sec] from now
+    ///
+    /// Note that for continuous smooth animation as a sequence of linear slides without pauses in between, you may want 'duration' to be slightly greater than the expected rate at which new values will arrive (example: every 0.2sec with 0.3sec max jitter on data source and network send plus receive task runtime, so set duration to 0.5). This keeps the animation smooth even when the data flow driving it and the computer rendering it are not smooth. The cost is you will be up to 0.3sec behind the latest value received, but this buffer time covers normally expected delays in receiving new values. If the value expected 0.2sec from the previous one receive time is more that 'transition_duration' (0.5sec) late, the animation will have time to complete and the value will appear to freeze until a new value arrives.
+    ///
+    /// `SampleAdapter` estimates the arrival rate and jitter of incoming samples and picks this duration automatically
+    ///
+    /// A target value or duration which is not accepted is ignored, leaving the current transition running. Use `try_start_transition()` to find out why
+    pub fn start_transition(
+        &mut self,
+        current_time: f64,
+        transition_duration: f64,
+        target_value: f32,
+    ) {
+        if let Err(e) = self.try_start_transition(current_time, transition_duration, target_value) {
+            debug!("{}, transition not started", e);
+        }
+    }
+
+    /// Start a transition as `start_transition()` does, or report why the target value or duration is not accepted
+    pub fn try_start_transition(
+        &mut self,
+        current_time: f64,
+        transition_duration: f64,
+        target_value: f32,
+    ) -> Result<(), ValueError> {
+        if self.frozen {
+            return Err(ValueError::Frozen);
+        }
+        if transition_duration.is_nan() || transition_duration < 0.0 {
+            return Err(ValueError::InvalidDuration(transition_duration));
+        }
+        let target_value = self.range_policy.accept(target_value)?;
+
+        let current_value = self.current_value(current_time);
+        trace!(
+            "Start transition current: {}  target: {}",
+            current_value,
+            target_value
+        );
+
+        self.current_transition = Transition::new(
+            self.animation_time(current_time),
+            transition_duration,
+            current_value,
+            target_value,
+        )
+        .with_easing(self.easing);
+        if let Some(observer) = &mut self.observer {
+            observer.transition_started(&self.current_transition);
+        }
+        self.record(SessionEvent::Transition {
+            time: current_time,
+            duration: transition_duration,
+            value: target_value,
+            easing: self.easing,
+        });
+
+        Ok(())
+    }
+
+    /// Start recording every transition started from 'current_time' [sec], replacing any recording in progress
+    ///
+    /// Stop and save the recording to replay the session exactly with a `SessionPlayer`
+    pub fn start_recording(&mut self, current_time: f64) -> &mut Self {
+        let mut session = Session::new(current_time, self.current_transition);
+        if let Some(paused_at) = self.paused_at {
+            session.push(SessionEvent::Pause { time: paused_at });
+        }
+        if self.frozen {
+            session.push(SessionEvent::Freeze {
+                time: current_time,
+                value: self.current_transition.end_value(),
+            });
+        }
+        self.recording = Some(session);
+
+        self
+    }
+
+    /// The recording in progress
+    pub fn recording(&self) -> Option<&Session> {
+        self.recording.as_ref()
+    }
+
+    /// Add an incoming sample to the recording in progress, if any
+    pub fn record_signal(&mut self, signal: &SignalSample) {
+        self.record(SessionEvent::Signal(signal.clone()));
+    }
+
+    /// End the recording in progress, returning it
+    pub fn stop_recording(&mut self) -> Option<Session> {
+        self.recording.take()
+    }
+
+    /// Take the animation, pause, freeze, recording and observer to carry over to another mandala, for example one rebuilt from a changed config
+    #[cfg(feature = "config")]
+    pub(crate) fn take_runtime_state(&mut self) -> RuntimeState {
+        RuntimeState {
+            transition: self.current_transition,
+            paused_at: self.paused_at,
+            frozen: self.frozen,
+            recording: self.recording.take(),
+            observer: self.observer.take(),
+        }
+    }
+
+    /// Carry on from the state taken from another mandala with `take_runtime_state()`
+    #[cfg(feature = "config")]
+    pub(crate) fn restore_runtime_state(&mut self, state: RuntimeState) {
+        self.current_transition = state.transition;
+        self.paused_at = state.paused_at;
+        self.frozen = state.frozen;
+        self.recording = state.recording;
+        self.observer = state.observer;
+    }
+
+    /// Get a [0.0..1.0] number representing %open of the mandala based on the transition rendering time, outside that range only as the range policy allows
+    pub fn current_value(&self, current_time: f64) -> f32 {
+        self.range_policy.apply(
+            self.current_transition
+                .value(self.animation_time(current_time)),
+        )
+    }
+
+    /// Get a [0.0..1.0] number representing %complete of the transition rendering time
+    pub fn current_percent(&self, current_time: f64) -> f32 {
+        self.current_transition
+            .percent(self.animation_time(current_time))
+    }
+
+    /// The transition the value is following, to carry the animation over to another mandala
+    pub fn current_transition(&self) -> Transition {
+        self.current_transition
+    }
+
+    /// Continue the animation of another mandala from its `current_transition()`
+    pub fn set_transition(&mut self, transition: Transition) -> &mut Self {
+        self.current_transition = transition;
+
+        self
+    }
+
+    /// Stop the animation at 'current_time' [sec], for example during a break in an experiment
+    ///
+    /// Transitions started while paused begin from the paused value and run once resumed
+    pub fn pause(&mut self, current_time: f64) -> &mut Self {
+        if self.paused_at.is_none() {
+            debug!("Paused at {}", current_time);
+            self.paused_at = Some(current_time);
+            self.record(SessionEvent::Pause { time: current_time });
+        }
+
+        self
+    }
+
+    /// Carry on the animation from where it was paused, with no jump
+    pub fn resume(&mut self, current_time: f64) -> &mut Self {
+        if let Some(paused_at) = self.paused_at.take() {
+            let pause = (current_time - paused_at).max(0.0);
+            self.current_transition = self.current_transition.delayed(pause);
+            self.petal_count_transition = self.petal_count_transition.delayed(pause);
+            debug!("Resumed at {} after {} sec", current_time, pause);
+            self.record(SessionEvent::Resume { time: current_time });
+        }
+
+        self
+    }
+
+    /// True between `pause()` and `resume()`
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Jump to 'value' [0.0-1.0] and hold it, ignoring new values until `unfreeze()`, for example to halt feedback at a neutral value
+    pub fn freeze_at(&mut self, current_time: f64, value: f32) -> Result<(), ValueError> {
+        let value = self.range_policy.accept(value)?;
+        self.current_transition = Transition::fixed_value(value);
+        self.frozen = true;
+        debug!("Frozen at {} with value {}", current_time, value);
+        self.record(SessionEvent::Freeze {
+            time: current_time,
+            value,
+        });
+
+        Ok(())
+    }
+
+    /// Accept new values again, animating on from the frozen value
+    pub fn unfreeze(&mut self, current_time: f64) -> &mut Self {
+        if self.frozen {
+            self.frozen = false;
+            debug!("Unfrozen at {}", current_time);
+            self.record(SessionEvent::Unfreeze { time: current_time });
+        }
+
+        self
+    }
+
+    /// True between `freeze_at()` and `unfreeze()`
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Follow 'transition', neither paused nor frozen, to replay a session from the start
+    pub(crate) fn replay_from(&mut self, transition: Transition) {
+        self.current_transition = transition;
+        self.paused_at = None;
+        self.frozen = false;
+    }
+
+    /// [sec] The time animation has reached, which stands still while paused
+    fn animation_time(&self, current_time: f64) -> f64 {
+        match self.paused_at {
+            Some(paused_at) => current_time.min(paused_at),
+            None => current_time,
+        }
+    }
+
+    fn record(&mut self, event: SessionEvent) {
+        if let Some(session) = &mut self.recording {
+            session.push(event);
+        }
+    }
+
+    /// Find the Tranform value from [start..end] using independent linear interpolation on each matrix element based on time
+    fn current_transform(
+        &self,
+        current_time: f64,
+        start: &Transform,
+        end: &Transform,
+    ) -> Transform {
+        *start + (*end - *start) * self.current_value(current_time)
+    }
+
+    /// Find the Color value from closed to open with independent linear interpolation of each RGBA value based on time, never beyond either
+    fn current_color(&self, current_time: f64) -> Color {
+        interpolate_color(
+            &self.mandala_state_closed.color,
+            &self.mandala_state_open.color,
+            self.current_value(current_time).clamp(0.0, 1.0),
+        )
+    }
+
+    /// Get the state of the mandala based on time and linear interpolation of all values between endpoints
+    fn current_state(&mut self, current_time: f64) -> MandalaState {
+        let color = self.current_color(current_time);
+        let petal_rotate_transform = self.current_transform(
+            current_time,
+            &self.mandala_state_open.petal_rotate_transform,
+            &self.mandala_state_closed.petal_rotate_transform,
+        );
+        let petal_scale_transform = self.current_transform(
+            current_time,
+            &self.mandala_state_open.petal_scale_transform,
+            &self.mandala_state_closed.petal_scale_transform,
+        );
+        let petal_translate_transform = self.current_transform(
+            current_time,
+            &self.mandala_state_open.petal_translate_transform,
+            &self.mandala_state_closed.petal_translate_transform,
+        );
+
+        MandalaState {
+            color,
+            petal_rotate_transform,
+            petal_scale_transform,
+            petal_translate_transform,
+        }
+    }
+
+    /// Render the interpolated current time state to the backend
+    pub fn draw(&mut self, current_time: f64, backend: &mut dyn Backend) {
+        let clock = RealTimeClock::new();
+        let mut frame = FrameStats {
+            time: current_time,
+            ..FrameStats::default()
+        };
+        self.for_each_shape(current_time, |mesh| {
+            frame.shapes += 1;
+            frame.triangles += mesh.triangle_count();
+            mesh.tesselate(backend);
+        });
+        if let Some(observer) = &mut self.observer {
+            frame.draw_time = clock.now();
+            observer.frame_drawn(&frame);
+        }
+    }
+
+    /// Report transitions and frames drawn to 'observer', replacing any observer already set
+    pub fn set_observer(&mut self, observer: impl MandalaObserver + Send + 'static) -> &mut Self {
+        self.observer = Some(Box::new(observer));
+
+        self
+    }
+
+    /// Stop reporting to the observer, returning it
+    pub fn remove_observer(&mut self) -> Option<Box<dyn MandalaObserver + Send>> {
+        self.observer.take()
+    }
+
+    /// The interpolated current time state as a standalone SVG document, one `<path>` per shape in drawing order
+    ///
+    /// The document is sized to fit the shapes, in the same coordinates as `draw()`
+    pub fn to_svg(&mut self, current_time: f64) -> String {
+        let mut elements = Vec::new();
+        let mut bounds: Option<(Vector, Vector)> = None;
+        self.for_each_shape(current_time, |mesh| {
+            elements.push(mesh.to_svg_element());
+            if let Some((min, max)) = mesh.bounds() {
+                bounds = Some(match bounds {
+                    None => (min, max),
+                    Some((all_min, all_max)) => (
+                        Vector::new(all_min.x.min(min.x), all_min.y.min(min.y)),
+                        Vector::new(all_max.x.max(max.x), all_max.y.max(max.y)),
+                    ),
+                });
+            }
+        });
+
+        svg_document(&elements, bounds)
+    }
+
+    /// Set the transform and color of each layer and petal for the current time, passing them to 'shape' in drawing order
+    fn for_each_shape(&mut self, current_time: f64, mut shape: impl FnMut(&mut MutableMesh)) {
+        let mandala_state = self.current_state(current_time);
+        let value = self.current_value(current_time);
+        let respacing_percent = self
+            .petal_count_transition
+            .percent(self.animation_time(current_time));
+
+        if let Some(background) = self.background.as_mut() {
+            shape(background.update(self.mandala_center, value));
+        }
+        if respacing_percent >= 1.0 {
+            // Petals which have finished shrinking out are no longer needed
+            self.petal_slots.retain(|slot| slot.end_presence > 0.0);
+        }
+
+        let angles: Vec<f32> = self
+            .petal_slots
+            .iter()
+            .map(|slot| self.angular_offset + slot.placement(respacing_percent).angle)
+            .collect();
+
+        // For each petal
+        for i in self.draw_order.sort(&angles) {
+            let slot = self.petal_slots[i];
+            let presence = slot.presence(respacing_percent);
+            if presence <= 0.0 {
+                continue;
+            }
+
+            self.petal.set_color(self.overlap.apply(Color {
+                a: mandala_state.color.a * presence,
+                ..mandala_state.color
+            }));
+            let placement = slot.placement(respacing_percent);
+            self.petal.set_transform(
+                self.mandala_center
+                    * Transform::rotate(self.angular_offset + placement.angle)
+                    * Transform::scale((1.0, placement.mirror))
+                    * Transform::translate((self.ring_radius + placement.radius, 0.0))
+                    * Transform::scale((presence, presence))
+                    * mandala_state.petal_translate_transform
+                    * mandala_state.petal_scale_transform
+                    * mandala_state.petal_rotate_transform,
+            );
+
+            shape(&mut self.petal);
+        }
+
+        if let Some(hub) = self.hub.as_mut() {
+            shape(hub.update(self.mandala_center, value));
+        }
+        if let Some(foreground) = self.foreground.as_mut() {
+            shape(foreground.update(self.mandala_center, value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mandala::{Mandala, MandalaState, RangePolicy, ValueError};
+    use crate::{color::Color, geom::Transform, mesh::MutableMesh};
+
+    fn mandala(value: f32) -> Mandala {
+        let state = |color| {
+            MandalaState::new(
+                color,
+                Transform::IDENTITY,
+                Transform::IDENTITY,
+                Transform::IDENTITY,
+            )
+        };
+        Mandala::with_petal(
+            MutableMesh::load("tests/petal.svg").unwrap(),
+            (0.0, 0.0),
+            (1.0, 1.0),
+            3,
+            state(Color::RED),
+            state(Color::YELLOW),
+            value,
+        )
+    }
+
+    #[test]
+    fn test_add_mandala_transforms() {
+        let left = Transform::IDENTITY;
+        let right = left * 2 - Transform::IDENTITY;
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn test_create_mandala_state() {
+        let _mandala_state_open = MandalaState {
+            color: Color::RED,
+            petal_rotate_transform: Transform::rotate(90),
+            petal_translate_transform: Transform::translate((50.0, 0.0)),
+            petal_scale_transform: Transform::scale((1.0, 1.0)),
+        };
+    }
+
+    #[test]
+    fn test_range_policy() {
+        let mut mandala = mandala(3.0);
+        assert_eq!(RangePolicy::Clamp, mandala.range_policy());
+        assert_eq!(1.0, mandala.current_value(0.0));
+        mandala.start_transition(1.0, 1.0, -2.0);
+        assert_eq!(0.0, mandala.current_transition().end_value());
+
+        mandala.set_range_policy(RangePolicy::Wrap).set_value(1.25);
+        assert_eq!(0.25, mandala.current_value(0.0));
+        mandala.set_value(1.0);
+        assert_eq!(1.0, mandala.current_value(0.0));
+
+        mandala
+            .set_range_policy(RangePolicy::Extrapolate)
+            .set_value(3.0);
+        assert_eq!(3.0, mandala.current_value(0.0));
+        assert_eq!(Color::RED, mandala.current_color(0.0));
+    }
+
+    #[test]
+    fn test_initial_value_is_accepted_like_set_value() {
+        assert_eq!(0.0, mandala(f32::NAN).current_value(0.0));
+        assert_eq!(0.0, mandala(f32::NEG_INFINITY).current_value(0.0));
+        assert_eq!(1.0, mandala(1.5).current_value(0.0));
+        let mut extrapolated = mandala(1.5);
+        extrapolated.set_range_policy(RangePolicy::Extrapolate);
+        assert_eq!(1.5, extrapolated.current_value(0.0));
+    }
+
+    #[test]
+    fn test_rejected_values_leave_mandala_unchanged() {
+        let mut mandala = mandala(0.5);
+        mandala.set_range_policy(RangePolicy::Error);
+        assert_eq!(
+            Err(ValueError::OutOfRange(1.5)),
+            mandala.try_start_transition(1.0, 1.0, 1.5)
+        );
+        assert_eq!(
+            Err(ValueError::NotFinite(f32::INFINITY)),
+            mandala.try_set_value(f32::INFINITY)
+        );
+        assert_eq!(
+            Err(ValueError::InvalidDuration(-1.0)),
+            mandala.try_start_transition(1.0, -1.0, 0.2)
+        );
+        mandala.start_transition(1.0, 1.0, f32::NAN);
+        assert_eq!(0.5, mandala.current_value(2.0));
+
+        assert_eq!(Ok(()), mandala.try_start_transition(1.0, 1.0, 1.0));
+        assert_eq!(0.5, mandala.current_value(0.0)); // Before the transition starts
+        assert_eq!(1.0, mandala.current_value(2.0));
+    }
+
+    #[test]
+    fn test_pause_and_resume_without_a_jump() {
+        let mut mandala = mandala(0.0);
+        mandala.start_transition(0.0, 4.0, 1.0);
+        mandala.pause(1.0);
+        assert!(mandala.is_paused());
+        assert_eq!(0.25, mandala.current_value(1.0));
+        assert_eq!(0.25, mandala.current_value(5.0));
+
+        mandala.resume(5.0);
+        assert!(!mandala.is_paused());
+        assert_eq!(0.25, mandala.current_value(5.0));
+        assert_eq!(0.5, mandala.current_value(6.0));
+        assert_eq!(1.0, mandala.current_value(8.0));
+
+        // A transition started while paused waits for resume
+        mandala.pause(10.0).start_transition(11.0, 1.0, 0.0);
+        assert_eq!(1.0, mandala.current_value(12.0));
+        mandala.resume(12.0);
+        assert_eq!(0.5, mandala.current_value(12.5));
+    }
+
+    #[test]
+    fn test_freeze_at_value() {
+        let mut mandala = mandala(0.0);
+        mandala.start_transition(0.0, 1.0, 1.0);
+        assert_eq!(Ok(()), mandala.freeze_at(0.5, 0.2));
+        assert!(mandala.is_frozen());
+        assert_eq!(
+            Err(ValueError::Frozen),
+            mandala.try_start_transition(1.0, 1.0, 1.0)
+        );
+        assert_eq!(0.2, mandala.current_value(2.0));
+
+        mandala.unfreeze(2.0).start_transition(2.0, 1.0, 0.6);
+        assert!((mandala.current_value(2.5) - 0.4).abs() < 1e-6);
+    }
+}
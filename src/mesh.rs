@@ -0,0 +1,134 @@
+//! A vector shape which can be moved, scaled and recolored each frame
+
+use crate::{
+    backend::{Backend, Vertex},
+    color::Color,
+    geom::{Transform, Vector},
+    svg::{load_path, path_element, path_to_svg_data, try_parse_path_from_svg_str, ParseError},
+};
+use lyon::{
+    math::Point,
+    path::Path,
+    tessellation::{BuffersBuilder, FillOptions, FillTessellator, FillVertex, VertexBuffers},
+};
+
+pub struct MutableMesh {
+    pub color: Color,
+    pub transform: Transform,
+    geometry: VertexBuffers<Point, u32>,
+    vertices: Vec<Vertex>,
+    path_data: String,
+}
+
+/// A renderable vector object from SVG with a runtime tranformation matrix
+impl MutableMesh {
+    /// Create a default with key values specified, panicking if the SVG can not be loaded
+    pub fn new(svg_file_name: &str) -> Self {
+        Self::load(svg_file_name).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a default with key values specified
+    pub fn load(svg_file_name: &str) -> Result<Self, ParseError> {
+        Self::try_from_path(&load_path(svg_file_name)?)
+            .map_err(|e| ParseError::new(format!("SVG file '{}': {}", svg_file_name, e)))
+    }
+
+    /// Create from the first path in the text of an SVG document, for example one embedded with `include_str!`
+    pub fn from_svg_str(svg_str: &str) -> Result<Self, ParseError> {
+        Self::try_from_path(&try_parse_path_from_svg_str(svg_str)?)
+    }
+
+    /// Create from a path which has already been loaded, panicking if it can not be tessellated
+    pub fn from_path(path: &Path) -> Self {
+        Self::try_from_path(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create from a path which has already been loaded
+    ///
+    /// The shape is tessellated into triangles once here, and only the corners are transformed each time it is drawn
+    pub fn try_from_path(path: &Path) -> Result<Self, ParseError> {
+        let mut geometry: VertexBuffers<Point, u32> = VertexBuffers::new();
+        FillTessellator::new()
+            .tessellate_path(
+                path,
+                &FillOptions::tolerance(0.01),
+                &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| vertex.position()),
+            )
+            .map_err(|e| ParseError::new(format!("Can not tessellate path: {:?}", e)))?;
+        let color = Color::RED; // Initial state will be overriden on first draw
+
+        Ok(Self {
+            color,
+            transform: Transform::IDENTITY,
+            geometry,
+            vertices: Vec::new(),
+            path_data: path_to_svg_data(path),
+        })
+    }
+
+    /// Replace the shape with the path from an SVG file, keeping the current transform and color
+    ///
+    /// If the file can not be loaded the shape is unchanged
+    pub fn reload(&mut self, svg_file_name: &str) -> Result<(), ParseError> {
+        let loaded = Self::load(svg_file_name)?;
+        self.geometry = loaded.geometry;
+        self.path_data = loaded.path_data;
+
+        Ok(())
+    }
+
+    /// Render the vector shape with current transform and color into screen triangles
+    pub fn tesselate(&mut self, backend: &mut dyn Backend) {
+        let transform = self.transform;
+        let color = self.color;
+        self.vertices.clear();
+        self.vertices
+            .extend(self.geometry.vertices.iter().map(|point| Vertex {
+                position: transform * Vector::new(point.x, point.y),
+                color,
+            }));
+
+        backend.draw_triangles(&self.vertices, &self.geometry.indices);
+    }
+
+    /// The shape with current transform and color as an SVG `<path>` element
+    pub fn to_svg_element(&self) -> String {
+        path_element(&self.path_data, &self.transform, &self.color)
+    }
+
+    /// The smallest rectangle (top left, bottom right) containing the shape with the current transform, or None if it is empty
+    pub fn bounds(&self) -> Option<(Vector, Vector)> {
+        let transform = self.transform;
+        self.geometry
+            .vertices
+            .iter()
+            .map(|point| transform * Vector::new(point.x, point.y))
+            .fold(None, |bounds, p| match bounds {
+                None => Some((p, p)),
+                Some((min, max)) => Some((
+                    Vector::new(min.x.min(p.x), min.y.min(p.y)),
+                    Vector::new(max.x.max(p.x), max.y.max(p.y)),
+                )),
+            })
+    }
+
+    /// Number of triangles drawn each time the shape is tesselated
+    pub fn triangle_count(&self) -> usize {
+        self.geometry.indices.len() / 3
+    }
+
+    /// This transform will be applied to all new shapes as well
+    /// Call tesselate() after all such mutations are complete
+    pub fn set_transform(&mut self, transform: Transform) -> &mut Self {
+        self.transform = transform;
+
+        self
+    }
+
+    /// Call tesselate() after all such mutations are complete
+    pub fn set_color(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+
+        self
+    }
+}
@@ -0,0 +1,82 @@
+//! Hooks to monitor a running mandala, for example to export metrics from an unattended installation
+//!
+//! Set an observer with `Mandala::set_observer()`. To read what it collected from elsewhere, share it as an `Arc<Mutex<_>>`
+
+use crate::animation::Transition;
+use std::sync::{Arc, Mutex};
+
+/// What one `Mandala::draw()` call drew, and how long it took
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// [sec] The animation time drawn
+    pub time: f64,
+    /// [sec] Wall time spent placing the shapes and passing them to the backend
+    pub draw_time: f64,
+    /// Petals and layers drawn
+    pub shapes: usize,
+    pub triangles: usize,
+}
+
+/// Receives events from a mandala. Each method does nothing unless implemented
+pub trait MandalaObserver {
+    /// A transition was accepted and has started
+    fn transition_started(&mut self, _transition: &Transition) {}
+
+    /// A frame was drawn
+    fn frame_drawn(&mut self, _frame: &FrameStats) {}
+}
+
+impl<T: MandalaObserver> MandalaObserver for Arc<Mutex<T>> {
+    fn transition_started(&mut self, transition: &Transition) {
+        if let Ok(mut observer) = self.lock() {
+            observer.transition_started(transition);
+        }
+    }
+
+    fn frame_drawn(&mut self, frame: &FrameStats) {
+        if let Ok(mut observer) = self.lock() {
+            observer.frame_drawn(frame);
+        }
+    }
+}
+
+/// Running totals of what a mandala has done, for a health check or metrics endpoint
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MandalaMetrics {
+    pub transitions: usize,
+    pub frames: usize,
+    /// The most recent frame drawn
+    pub last_frame: Option<FrameStats>,
+    /// [sec] Total wall time spent drawing
+    pub total_draw_time: f64,
+    /// [sec] The slowest frame
+    pub max_draw_time: f64,
+}
+
+impl MandalaMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// [sec] Mean wall time spent drawing a frame
+    pub fn mean_draw_time(&self) -> f64 {
+        if self.frames > 0 {
+            self.total_draw_time / self.frames as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+impl MandalaObserver for MandalaMetrics {
+    fn transition_started(&mut self, _transition: &Transition) {
+        self.transitions += 1;
+    }
+
+    fn frame_drawn(&mut self, frame: &FrameStats) {
+        self.frames += 1;
+        self.last_frame = Some(*frame);
+        self.total_draw_time += frame.draw_time;
+        self.max_draw_time = self.max_draw_time.max(frame.draw_time);
+    }
+}
@@ -0,0 +1,660 @@
+//! Receive Open Sound Control (OSC) messages over UDP and route them to named signals
+//!
+//! EEG apps such as Mind Monitor publish band powers as OSC, for example `/muse/elements/alpha_absolute`. Messages and bundles are parsed here with no extra dependencies
+
+use crate::{input::SignalSample, timeline::Sample};
+use std::{
+    error::Error,
+    fmt, io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+/// The largest IPv4 UDP payload
+const MAX_PACKET_SIZE: usize = 65_507;
+const BUNDLE_TAG: &[u8] = b"#bundle\0";
+/// The bundle time tag meaning "as soon as it arrives"
+pub const IMMEDIATELY: u64 = 1;
+
+/// Why OSC packets could not be received
+#[derive(Debug)]
+pub enum OscError {
+    Io(io::Error),
+    /// The bytes are not a valid OSC packet, with a description of why
+    Parse(String),
+}
+
+impl fmt::Display for OscError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OscError::Io(e) => write!(f, "Can not receive OSC: {}", e),
+            OscError::Parse(message) => write!(f, "Can not parse OSC: {}", message),
+        }
+    }
+}
+
+impl Error for OscError {}
+
+impl From<io::Error> for OscError {
+    fn from(e: io::Error) -> Self {
+        OscError::Io(e)
+    }
+}
+
+/// One argument of a message, named after its OSC type
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    String(String),
+    Blob(Vec<u8>),
+    Long(i64),
+    Double(f64),
+    TimeTag(u64),
+    Char(char),
+    /// RGBA, 8 bits each with red in the high byte
+    Color(u32),
+    /// Port id, status byte, data 1, data 2
+    Midi([u8; 4]),
+    True,
+    False,
+    Nil,
+    Infinitum,
+}
+
+impl OscArg {
+    /// The argument as a number, if it is one. `True` is 1.0 and `False` is 0.0
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            OscArg::Int(i) => Some(i as f32),
+            OscArg::Float(f) => Some(f),
+            OscArg::Long(i) => Some(i as f32),
+            OscArg::Double(d) => Some(d as f32),
+            OscArg::True => Some(1.0),
+            OscArg::False => Some(0.0),
+            _ => None,
+        }
+    }
+
+    fn type_tag(&self) -> u8 {
+        match self {
+            OscArg::Int(_) => b'i',
+            OscArg::Float(_) => b'f',
+            OscArg::String(_) => b's',
+            OscArg::Blob(_) => b'b',
+            OscArg::Long(_) => b'h',
+            OscArg::Double(_) => b'd',
+            OscArg::TimeTag(_) => b't',
+            OscArg::Char(_) => b'c',
+            OscArg::Color(_) => b'r',
+            OscArg::Midi(_) => b'm',
+            OscArg::True => b'T',
+            OscArg::False => b'F',
+            OscArg::Nil => b'N',
+            OscArg::Infinitum => b'I',
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            OscArg::Int(i) => out.extend_from_slice(&i.to_be_bytes()),
+            OscArg::Float(f) => out.extend_from_slice(&f.to_be_bytes()),
+            OscArg::String(s) => write_string(out, s.as_bytes()),
+            OscArg::Blob(bytes) => {
+                out.extend_from_slice(&(bytes.len() as i32).to_be_bytes());
+                out.extend_from_slice(bytes);
+                pad(out);
+            }
+            OscArg::Long(i) => out.extend_from_slice(&i.to_be_bytes()),
+            OscArg::Double(d) => out.extend_from_slice(&d.to_be_bytes()),
+            OscArg::TimeTag(t) => out.extend_from_slice(&t.to_be_bytes()),
+            OscArg::Char(c) => out.extend_from_slice(&(*c as u32).to_be_bytes()),
+            OscArg::Color(rgba) => out.extend_from_slice(&rgba.to_be_bytes()),
+            OscArg::Midi(bytes) => out.extend_from_slice(bytes),
+            OscArg::True | OscArg::False | OscArg::Nil | OscArg::Infinitum => (),
+        }
+    }
+}
+
+/// An address such as `/muse/elements/alpha_absolute` and its arguments
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+impl OscMessage {
+    pub fn new(address: impl Into<String>, args: Vec<OscArg>) -> Self {
+        Self {
+            address: address.into(),
+            args,
+        }
+    }
+}
+
+/// The contents of one UDP datagram
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscPacket {
+    Message(OscMessage),
+    /// Messages and nested bundles to be handled together at 'time_tag', NTP format or `IMMEDIATELY`
+    Bundle {
+        time_tag: u64,
+        packets: Vec<OscPacket>,
+    },
+}
+
+impl OscPacket {
+    pub fn parse(bytes: &[u8]) -> Result<Self, OscError> {
+        let mut reader = Reader::new(bytes);
+        if bytes.starts_with(BUNDLE_TAG) {
+            reader.take(BUNDLE_TAG.len())?;
+            let time_tag = reader.u64()?;
+            let mut packets = Vec::new();
+            while !reader.is_empty() {
+                let size = reader.size()?;
+                if size % 4 != 0 {
+                    return Err(parse_error("bundle element size is not a multiple of 4"));
+                }
+                packets.push(Self::parse(reader.take(size)?)?);
+            }
+
+            Ok(OscPacket::Bundle { time_tag, packets })
+        } else {
+            Ok(OscPacket::Message(reader.message()?))
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            OscPacket::Message(message) => {
+                write_string(&mut out, message.address.as_bytes());
+                let mut tags = vec![b','];
+                tags.extend(message.args.iter().map(OscArg::type_tag));
+                write_string(&mut out, &tags);
+                for arg in &message.args {
+                    arg.write(&mut out);
+                }
+            }
+            OscPacket::Bundle { time_tag, packets } => {
+                out.extend_from_slice(BUNDLE_TAG);
+                out.extend_from_slice(&time_tag.to_be_bytes());
+                for packet in packets {
+                    let bytes = packet.to_bytes();
+                    out.extend_from_slice(&(bytes.len() as i32).to_be_bytes());
+                    out.extend_from_slice(&bytes);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Every message in the packet, including those in nested bundles, in order
+    pub fn messages(&self) -> Vec<&OscMessage> {
+        match self {
+            OscPacket::Message(message) => vec![message],
+            OscPacket::Bundle { packets, .. } => packets.iter().flat_map(Self::messages).collect(),
+        }
+    }
+}
+
+fn parse_error(message: impl Into<String>) -> OscError {
+    OscError::Parse(message.into())
+}
+
+/// Append a null terminated string padded to a multiple of 4 bytes
+fn write_string(out: &mut Vec<u8>, s: &[u8]) {
+    out.extend_from_slice(s);
+    out.push(0);
+    pad(out);
+}
+
+fn pad(out: &mut Vec<u8>) {
+    out.resize((out.len() + 3) / 4 * 4, 0);
+}
+
+/// Big-endian values from a packet, each padded to a multiple of 4 bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], OscError> {
+        let end = self.position + length;
+        if end > self.bytes.len() {
+            return Err(parse_error(format!(
+                "{} bytes needed at byte {} of {}",
+                length,
+                self.position,
+                self.bytes.len()
+            )));
+        }
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+
+        Ok(taken)
+    }
+
+    fn skip_padding(&mut self) -> Result<(), OscError> {
+        let padding = (4 - self.position % 4) % 4;
+        self.take(padding).map(|_| ())
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], OscError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+
+        Ok(array)
+    }
+
+    fn u32(&mut self) -> Result<u32, OscError> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, OscError> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    /// A non-negative length in bytes
+    fn size(&mut self) -> Result<usize, OscError> {
+        let size = i32::from_be_bytes(self.array()?);
+        if size < 0 {
+            return Err(parse_error(format!("negative size {}", size)));
+        }
+
+        Ok(size as usize)
+    }
+
+    fn string(&mut self) -> Result<String, OscError> {
+        let rest = &self.bytes[self.position.min(self.bytes.len())..];
+        let length = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| parse_error("string is not null terminated"))?;
+        let s = String::from_utf8(self.take(length)?.to_vec())
+            .map_err(|_| parse_error("string is not UTF-8"))?;
+        self.take(1)?;
+        self.skip_padding()?;
+
+        Ok(s)
+    }
+
+    fn message(&mut self) -> Result<OscMessage, OscError> {
+        let address = self.string()?;
+        if !address.starts_with('/') {
+            return Err(parse_error(format!(
+                "address '{}' does not start with '/'",
+                address
+            )));
+        }
+        if self.is_empty() {
+            return Ok(OscMessage::new(address, Vec::new())); // Older senders may omit the type tags
+        }
+        let tags = self.string()?;
+        if !tags.starts_with(',') {
+            return Err(parse_error(format!(
+                "type tags '{}' do not start with ','",
+                tags
+            )));
+        }
+        let mut args = Vec::new();
+        for tag in tags.bytes().skip(1) {
+            let arg = match tag {
+                b'i' => OscArg::Int(i32::from_be_bytes(self.array()?)),
+                b'f' => OscArg::Float(f32::from_be_bytes(self.array()?)),
+                b's' | b'S' => OscArg::String(self.string()?),
+                b'b' => {
+                    let size = self.size()?;
+                    let blob = self.take(size)?.to_vec();
+                    self.skip_padding()?;
+                    OscArg::Blob(blob)
+                }
+                b'h' => OscArg::Long(i64::from_be_bytes(self.array()?)),
+                b'd' => OscArg::Double(f64::from_be_bytes(self.array()?)),
+                b't' => OscArg::TimeTag(self.u64()?),
+                b'c' => OscArg::Char(
+                    std::char::from_u32(self.u32()?)
+                        .ok_or_else(|| parse_error("invalid char argument"))?,
+                ),
+                b'r' => OscArg::Color(self.u32()?),
+                b'm' => OscArg::Midi(self.array()?),
+                b'T' => OscArg::True,
+                b'F' => OscArg::False,
+                b'N' => OscArg::Nil,
+                b'I' => OscArg::Infinitum,
+                b'[' | b']' => continue, // Array contents are flattened into the arguments
+                _ => return Err(parse_error(format!("unknown type tag '{}'", tag as char))),
+            };
+            args.push(arg);
+        }
+
+        Ok(OscMessage::new(address, args))
+    }
+}
+
+/// Whether an OSC 'address' matches 'pattern', which may contain `?`, `*`, `[a-z]`, `[!abc]` and `{alpha,beta}` wildcards. Wildcards never match across a `/`
+pub fn address_matches(pattern: &str, address: &str) -> bool {
+    matches_from(pattern.as_bytes(), address.as_bytes())
+}
+
+fn matches_from(pattern: &[u8], address: &[u8]) -> bool {
+    let next = address.first().copied();
+    match pattern.first() {
+        None => address.is_empty(),
+        Some(b'*') => (0..=address.len())
+            .take_while(|i| *i == 0 || address[i - 1] != b'/')
+            .any(|i| matches_from(&pattern[1..], &address[i..])),
+        Some(b'?') => {
+            next.map_or(false, |c| c != b'/') && matches_from(&pattern[1..], &address[1..])
+        }
+        Some(b'[') => match pattern.iter().position(|c| *c == b']') {
+            Some(end) => {
+                next.map_or(false, |c| c != b'/' && in_set(&pattern[1..end], c))
+                    && matches_from(&pattern[end + 1..], &address[1..])
+            }
+            None => false,
+        },
+        Some(b'{') => match pattern.iter().position(|c| *c == b'}') {
+            Some(end) => pattern[1..end].split(|c| *c == b',').any(|choice| {
+                address.starts_with(choice)
+                    && matches_from(&pattern[end + 1..], &address[choice.len()..])
+            }),
+            None => false,
+        },
+        Some(c) => next == Some(*c) && matches_from(&pattern[1..], &address[1..]),
+    }
+}
+
+/// Whether 'c' is in the characters and ranges of a `[...]` wildcard
+fn in_set(set: &[u8], c: u8) -> bool {
+    let (negate, set) = match set.split_first() {
+        Some((b'!', rest)) => (true, rest),
+        _ => (false, set),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == b'-' {
+            found |= set[i] <= c && c <= set[i + 2];
+            i += 3;
+        } else {
+            found |= set[i] == c;
+            i += 1;
+        }
+    }
+
+    found != negate
+}
+
+/// Which messages feed a signal and how their arguments become its value
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscRoute {
+    pattern: String,
+    signal: String,
+    argument: Option<usize>, // None is the mean of all numeric arguments
+    range: Option<(f32, f32)>,
+}
+
+impl OscRoute {
+    /// Messages with an address matching 'pattern' become samples of 'signal' using their first argument as is
+    pub fn new(pattern: impl Into<String>, signal: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            signal: signal.into(),
+            argument: Some(0),
+            range: None,
+        }
+    }
+
+    /// Use argument 'index', counting from 0
+    pub fn with_argument(mut self, index: usize) -> Self {
+        self.argument = Some(index);
+
+        self
+    }
+
+    /// Use the mean of all numeric arguments, such as one band power per headset electrode
+    pub fn with_mean_of_arguments(mut self) -> Self {
+        self.argument = None;
+
+        self
+    }
+
+    /// Scale values so 'min' becomes 0.0 and 'max' becomes 1.0
+    ///
+    /// A range of zero width, or with a NaN or infinite end, can not scale anything and is ignored
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        let width = max - min;
+        if width != 0.0 && width.is_finite() {
+            self.range = Some((min, max));
+        }
+
+        self
+    }
+
+    pub fn signal(&self) -> &str {
+        &self.signal
+    }
+
+    /// The signal value of a message, or None if the address does not match or there is no number to use
+    ///
+    /// Non-numeric and non-finite arguments, as sent for a headset electrode with poor contact, are ignored
+    pub fn value(&self, message: &OscMessage) -> Option<f32> {
+        if !address_matches(&self.pattern, &message.address) {
+            return None;
+        }
+        let number = |arg: &OscArg| arg.as_f32().filter(|v| v.is_finite());
+        let value = match self.argument {
+            Some(index) => number(message.args.get(index)?)?,
+            None => {
+                let numbers: Vec<f32> = message.args.iter().filter_map(number).collect();
+                if numbers.is_empty() {
+                    return None;
+                }
+                numbers.iter().sum::<f32>() / numbers.len() as f32
+            }
+        };
+
+        Some(match self.range {
+            Some((min, max)) => (value - min) / (max - min),
+            None => value,
+        })
+    }
+}
+
+/// Listens for OSC packets on a UDP port without blocking, so it can be polled once per frame
+#[derive(Debug)]
+pub struct OscReceiver {
+    socket: UdpSocket,
+    routes: Vec<OscRoute>,
+    buffer: Vec<u8>,
+    rejected_packets: usize,
+}
+
+impl OscReceiver {
+    /// Listen on an address such as `0.0.0.0:5000`, the Mind Monitor default
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, OscError> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            routes: Vec::new(),
+            buffer: vec![0; MAX_PACKET_SIZE],
+            rejected_packets: 0,
+        })
+    }
+
+    /// The address being listened on, with the port chosen if bound to port 0
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Add a route. A message matching more than one route produces a sample for each
+    pub fn route(&mut self, route: OscRoute) -> &mut Self {
+        self.routes.push(route);
+
+        self
+    }
+
+    /// How many received packets were not valid OSC
+    pub fn rejected_packets(&self) -> usize {
+        self.rejected_packets
+    }
+
+    /// Read every packet which has arrived, returning a sample at 'time' [sec] for each routed message
+    ///
+    /// Bundle time tags are ignored, so samples keep the order they arrived in. Packets which are not valid OSC are skipped and counted in `rejected_packets()`
+    pub fn poll(&mut self, time: f64) -> Result<Vec<SignalSample>, OscError> {
+        let mut samples = Vec::new();
+        loop {
+            let length = match self.socket.recv(&mut self.buffer) {
+                Ok(length) => length,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            match OscPacket::parse(&self.buffer[..length]) {
+                Ok(packet) => samples.extend(self.route_packet(&packet, time)),
+                Err(_) => self.rejected_packets += 1,
+            }
+        }
+
+        Ok(samples)
+    }
+
+    /// The samples at 'time' [sec] routed from the messages in a packet
+    pub fn route_packet(&self, packet: &OscPacket, time: f64) -> Vec<SignalSample> {
+        let mut samples = Vec::new();
+        for message in packet.messages() {
+            for route in &self.routes {
+                if let Some(value) = route.value(message) {
+                    samples.push(SignalSample::new(route.signal(), Sample::new(time, value)));
+                }
+            }
+        }
+
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osc::{address_matches, OscArg, OscMessage, OscPacket, OscRoute, IMMEDIATELY};
+
+    fn alpha(values: &[f32]) -> OscMessage {
+        OscMessage::new(
+            "/muse/elements/alpha_absolute",
+            values.iter().map(|v| OscArg::Float(*v)).collect(),
+        )
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        let message = OscMessage::new(
+            "/test",
+            vec![
+                OscArg::Int(-3),
+                OscArg::Float(0.5),
+                OscArg::String("abcd".to_string()),
+                OscArg::Blob(vec![1, 2, 3]),
+                OscArg::Long(1 << 40),
+                OscArg::Double(0.25),
+                OscArg::TimeTag(IMMEDIATELY),
+                OscArg::Char('x'),
+                OscArg::Color(0xff00_80ff),
+                OscArg::Midi([0, 0x90, 60, 127]),
+                OscArg::True,
+                OscArg::False,
+                OscArg::Nil,
+                OscArg::Infinitum,
+            ],
+        );
+        let bytes = OscPacket::Message(message.clone()).to_bytes();
+        assert_eq!(0, bytes.len() % 4);
+        assert_eq!(
+            OscPacket::Message(message),
+            OscPacket::parse(&bytes).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_message_bytes() {
+        let bytes = OscPacket::Message(OscMessage::new("/a", vec![OscArg::Int(1)])).to_bytes();
+        assert_eq!(b"/a\0\0,i\0\0\0\0\0\x01".to_vec(), bytes);
+    }
+
+    #[test]
+    fn test_nested_bundle() {
+        let packet = OscPacket::Bundle {
+            time_tag: IMMEDIATELY,
+            packets: vec![
+                OscPacket::Message(alpha(&[0.1])),
+                OscPacket::Bundle {
+                    time_tag: IMMEDIATELY,
+                    packets: vec![OscPacket::Message(alpha(&[0.2]))],
+                },
+            ],
+        };
+        let parsed = OscPacket::parse(&packet.to_bytes()).unwrap();
+        assert_eq!(packet, parsed);
+        assert_eq!(vec![&alpha(&[0.1]), &alpha(&[0.2])], parsed.messages());
+    }
+
+    #[test]
+    fn test_invalid_packets() {
+        assert!(OscPacket::parse(b"").is_err());
+        assert!(OscPacket::parse(b"/a\0\0,i\0\0\0\0").is_err()); // Truncated argument
+        assert!(OscPacket::parse(b"/a\0\0,z\0\0").is_err());
+        assert!(OscPacket::parse(b"abc\0").is_err());
+        assert!(OscPacket::parse(b"#bundle\0\0\0\0\0\0\0\0\x01\0\0\0\x10/a\0\0").is_err());
+    }
+
+    #[test]
+    fn test_address_patterns() {
+        let address = "/muse/elements/alpha_absolute";
+        assert!(address_matches(address, address));
+        assert!(address_matches("/muse/elements/*_absolute", address));
+        assert!(address_matches("/muse/*/alpha_absolute", address));
+        assert!(address_matches(
+            "/muse/elements/{alpha,beta}_absolute",
+            address
+        ));
+        assert!(address_matches(
+            "/muse/elements/[a-c]lpha_?bsolute",
+            address
+        ));
+        assert!(!address_matches(
+            "/muse/elements/[!a]lpha_absolute",
+            address
+        ));
+        assert!(!address_matches("/muse/*", address)); // Wildcards stay within one part
+        assert!(!address_matches("/muse/elements/beta_absolute", address));
+        assert!(!address_matches("/muse/elements/alpha", address));
+    }
+
+    #[test]
+    fn test_route_value() {
+        let message = alpha(&[0.2, 0.4, f32::NAN, 0.6]);
+        let route = OscRoute::new("/muse/elements/alpha_absolute", "alpha");
+        assert_eq!(Some(0.2), route.value(&message));
+        assert_eq!(None, route.clone().with_argument(2).value(&message));
+        assert_eq!(None, route.clone().with_argument(9).value(&message));
+        let mean = route.clone().with_mean_of_arguments().value(&message);
+        assert!((mean.unwrap() - 0.4).abs() < 1e-6);
+        let flat = route.clone().with_argument(3).with_range(1.0, 1.0);
+        assert_eq!(Some(0.6), flat.value(&message)); // Left unscaled
+        let ranged = route.with_argument(3).with_range(-1.0, 1.0).value(&message);
+        assert!((ranged.unwrap() - 0.8).abs() < 1e-6);
+        assert_eq!(None, OscRoute::new("/other", "alpha").value(&message));
+    }
+}
@@ -0,0 +1,282 @@
+//! Built-in mandala designs with their shapes embedded, as a starting point for new projects and stable test fixtures
+
+use crate::{
+    color::Color,
+    geom::{Transform, Vector},
+    layout::{PetalDrawOrder, PetalLayout, PetalOverlap},
+    mandala::{LayerKind, LayerState, Mandala, MandalaLayer, MandalaState},
+    mesh::MutableMesh,
+};
+use std::{fmt, str::FromStr};
+
+const LOTUS_PETAL: &str = include_str!("../static/petal.svg");
+const SUNFLOWER_PETAL: &str = include_str!("../static/presets/sunflower_petal.svg");
+const SUNFLOWER_HUB: &str = include_str!("../static/presets/sunflower_hub.svg");
+const STAR_PETAL: &str = include_str!("../static/presets/star_petal.svg");
+const ROSE_WINDOW_PETAL: &str = include_str!("../static/presets/rose_window_petal.svg");
+const ROSE_WINDOW_HUB: &str = include_str!("../static/presets/rose_window_hub.svg");
+
+/// A named built-in design
+///
+/// At a scale of 1.0 each fits in about 500 x 500 px when fully open
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Preset {
+    /// Crimson petals which close to translucent turquoise, as in the mandala example
+    Lotus,
+    /// Golden petals in a phyllotaxis spiral around a brown seed head
+    Sunflower,
+    /// Sharp silver points which open to gold
+    Star,
+    /// Stained-glass blue and violet blades mirrored around a ruby center
+    RoseWindow,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        Preset::Lotus,
+        Preset::Sunflower,
+        Preset::Star,
+        Preset::RoseWindow,
+    ];
+
+    /// The name used by `from_name()` and in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Lotus => "lotus",
+            Preset::Sunflower => "sunflower",
+            Preset::Star => "star",
+            Preset::RoseWindow => "rose_window",
+        }
+    }
+
+    /// The preset with this name, ignoring case and treating '-' or ' ' like '_'
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase().replace(['-', ' '], "_");
+
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.name() == name)
+    }
+
+    /// The SVG document of the petal shape
+    pub fn petal_svg(&self) -> &'static str {
+        match self {
+            Preset::Lotus => LOTUS_PETAL,
+            Preset::Sunflower => SUNFLOWER_PETAL,
+            Preset::Star => STAR_PETAL,
+            Preset::RoseWindow => ROSE_WINDOW_PETAL,
+        }
+    }
+
+    /// The solid color the design is meant to be drawn over
+    pub fn background(&self) -> Color {
+        match self {
+            Preset::Lotus | Preset::Star => Color::BLACK,
+            Preset::Sunflower => Color::rgb(0.05, 0.15, 0.3),
+            Preset::RoseWindow => Color::rgb(0.08, 0.06, 0.1),
+        }
+    }
+
+    /// Create the mandala centered at 'screen_position' and starting at 'value' [0.0..1.0] open
+    pub fn build(&self, screen_position: impl Into<Vector>, scale: f32, value: f32) -> Mandala {
+        let petal = embedded_mesh(self.petal_svg());
+        let background = self.background();
+        let mut mandala = match self {
+            Preset::Lotus => {
+                let mut mandala = Mandala::with_petal(
+                    petal,
+                    screen_position,
+                    (scale * 0.8, scale * 0.8),
+                    20,
+                    MandalaState::new(
+                        Color::rgb(220.0 / 255.0, 20.0 / 255.0, 60.0 / 255.0),
+                        Transform::rotate(90),
+                        Transform::translate((50.0, 0.0)),
+                        Transform::scale((1.0, 1.0)),
+                    ),
+                    MandalaState::new(
+                        Color::rgb(64.0 / 255.0, 224.0 / 255.0, 208.0 / 255.0).with_alpha(0.2),
+                        Transform::rotate(0.0),
+                        Transform::translate((0.0, 0.0)),
+                        Transform::scale((0.1, 1.0)),
+                    ),
+                    value,
+                );
+                mandala.set_draw_order(PetalDrawOrder::ByAngle { axis: 270.0 });
+
+                mandala
+            }
+            Preset::Sunflower => {
+                let mut mandala = Mandala::with_petal(
+                    petal,
+                    screen_position,
+                    (scale, scale),
+                    34,
+                    MandalaState::new(
+                        Color::rgb(1.0, 0.8, 0.1),
+                        Transform::rotate(90),
+                        Transform::translate((0.0, 0.0)),
+                        Transform::scale((0.75, 0.75)),
+                    ),
+                    MandalaState::new(
+                        Color::rgb(0.6, 0.45, 0.1).with_alpha(0.6),
+                        Transform::rotate(90),
+                        Transform::translate((0.0, 0.0)),
+                        Transform::scale((0.45, 0.5)),
+                    ),
+                    value,
+                );
+                mandala.set_layout(PetalLayout::Spiral { spacing: 10.0 }, 0.0, 0.0);
+                mandala.set_ring(40.0, 0.0).set_layer(
+                    LayerKind::Hub,
+                    MandalaLayer::with_mesh(
+                        embedded_mesh(SUNFLOWER_HUB),
+                        LayerState::new(Color::rgb(0.35, 0.2, 0.05), Transform::IDENTITY),
+                        LayerState::new(Color::rgb(0.25, 0.15, 0.05), Transform::scale((1.3, 1.3))),
+                    ),
+                );
+
+                mandala
+            }
+            Preset::Star => Mandala::with_petal(
+                petal,
+                screen_position,
+                (scale, scale),
+                8,
+                MandalaState::new(
+                    Color::rgb(1.0, 0.84, 0.0),
+                    Transform::rotate(90),
+                    Transform::translate((20.0, 0.0)),
+                    Transform::scale((1.0, 1.1)),
+                ),
+                MandalaState::new(
+                    Color::rgb(0.75, 0.75, 0.8).with_alpha(0.5),
+                    Transform::rotate(90),
+                    Transform::translate((0.0, 0.0)),
+                    Transform::scale((0.3, 0.5)),
+                ),
+                value,
+            ),
+            Preset::RoseWindow => {
+                let mut mandala = Mandala::with_petal(
+                    petal,
+                    screen_position,
+                    (scale, scale),
+                    16,
+                    MandalaState::new(
+                        Color::rgb(0.2, 0.35, 0.9).with_alpha(0.8),
+                        Transform::rotate(90),
+                        Transform::translate((10.0, 0.0)),
+                        Transform::scale((1.0, 1.0)),
+                    ),
+                    MandalaState::new(
+                        Color::rgb(0.5, 0.15, 0.7).with_alpha(0.5),
+                        Transform::rotate(60),
+                        Transform::translate((0.0, 0.0)),
+                        Transform::scale((0.7, 0.6)),
+                    ),
+                    value,
+                );
+                mandala.set_layout(PetalLayout::Kaleidoscope { offset: 0.2 }, 0.0, 0.0);
+                mandala
+                    .set_ring(40.0, 0.0)
+                    .set_draw_order(PetalDrawOrder::Alternating)
+                    .set_layer(
+                        LayerKind::Hub,
+                        MandalaLayer::with_mesh(
+                            embedded_mesh(ROSE_WINDOW_HUB),
+                            LayerState::new(Color::rgb(0.8, 0.05, 0.15), Transform::IDENTITY),
+                            LayerState::new(
+                                Color::rgb(0.5, 0.05, 0.2),
+                                Transform::scale((0.8, 0.8)),
+                            ),
+                        ),
+                    );
+
+                mandala
+            }
+        };
+        mandala.set_overlap(PetalOverlap::Flatten { background });
+
+        mandala
+    }
+}
+
+/// The embedded shapes are checked by the tests, so failing to load one is a bug
+fn embedded_mesh(svg_str: &str) -> MutableMesh {
+    MutableMesh::from_svg_str(svg_str).expect("Built-in preset SVG")
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The name of a preset which does not exist
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownPreset(pub String);
+
+impl fmt::Display for UnknownPreset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = Preset::ALL.iter().map(Preset::name).collect();
+        write!(
+            f,
+            "Unknown preset '{}', expected one of {}",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownPreset {}
+
+impl FromStr for Preset {
+    type Err = UnknownPreset;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::from_name(name).ok_or_else(|| UnknownPreset(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::MeshBuffer;
+    use crate::presets::Preset;
+
+    #[test]
+    fn test_preset_names() {
+        for preset in Preset::ALL.iter() {
+            assert_eq!(Some(*preset), Preset::from_name(preset.name()));
+            assert_eq!(Ok(*preset), preset.to_string().parse());
+        }
+        assert_eq!(Some(Preset::RoseWindow), Preset::from_name("Rose Window"));
+        assert!("tulip".parse::<Preset>().is_err());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_preset_serializes_by_name() {
+        let json = serde_json::to_string(&Preset::RoseWindow).unwrap();
+        assert_eq!("\"rose_window\"", json);
+        assert_eq!(
+            Preset::RoseWindow,
+            serde_json::from_str::<Preset>(&json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_every_preset_draws() {
+        for preset in Preset::ALL.iter() {
+            let mut mesh = MeshBuffer::new();
+            preset.build((250, 250), 1.0, 0.5).draw(0.1, &mut mesh);
+            assert!(mesh.triangle_count() > 0, "{}", preset);
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Neurofeedback training protocols, which reward a metric such as alpha band power for beating an adaptive threshold
+//!
+//! The threshold is a percentile of the metric over the last few seconds, so it follows the participant as they improve or tire and a session is neither impossible nor trivial
+
+use crate::{input::SampleAdapter, mandala::Mandala, timeline::Sample};
+use std::collections::VecDeque;
+
+/// Samples needed in the baseline before any reward is given
+const MIN_BASELINE_SAMPLES: usize = 10;
+/// The highest threshold percentile, below 1.0 so the best values can still beat it
+const MAX_PERCENTILE: f32 = 0.99;
+
+/// Which way the metric should move to earn a reward
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RewardDirection {
+    /// Reward values above the threshold, for example to train up alpha
+    #[default]
+    Above,
+    /// Reward values below the threshold, for example to train down theta
+    Below,
+}
+
+/// The protocol's response to one metric sample
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reward {
+    /// [sec] Time of the sample
+    pub time: f64,
+    /// [0.0..1.0] 0.0 at or below the threshold, rising to 1.0 for a value beyond everything in the baseline
+    pub value: f32,
+    /// The metric value which must be beaten, or None while the baseline is still being collected
+    pub threshold: Option<f32>,
+    /// True when this sample completes a success, the threshold having been beaten for the whole hold time
+    pub success: bool,
+}
+
+/// A summary of a training session so far
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProtocolStats {
+    pub samples: usize,
+    /// [sec] From the first sample to the last
+    pub duration: f64,
+    /// [sec] Time spent beating the threshold
+    pub rewarded_time: f64,
+    /// [0.0..1.0] Mean reward over every sample
+    pub mean_reward: f32,
+    /// Mean of every raw metric value
+    pub mean_metric: f32,
+    /// Mean threshold over the samples once the baseline was collected
+    pub mean_threshold: Option<f32>,
+    /// [sec] When each success happened
+    pub success_times: Vec<f64>,
+}
+
+impl ProtocolStats {
+    /// [0.0..1.0] How much of the session was spent beating the threshold
+    pub fn rewarded_fraction(&self) -> f64 {
+        if self.duration > 0.0 {
+            self.rewarded_time / self.duration
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Turns a raw metric into a reward and success events to open a mandala with
+#[derive(Clone, Debug)]
+pub struct RewardProtocol {
+    direction: RewardDirection,
+    window: f64,
+    percentile: f32,
+    hold_time: f64,
+    baseline: VecDeque<Sample>,
+    rewarded_since: Option<f64>, // When the threshold was last beaten after not being beaten, or after the last success
+    last: Option<Reward>,
+    first_time: Option<f64>,
+    reward_total: f32,
+    metric_total: f32,
+    threshold_total: f32,
+    threshold_count: usize,
+    stats: ProtocolStats,
+}
+
+impl Default for RewardProtocol {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RewardProtocol {
+    /// Reward values above the 70th percentile of the last 30 sec, with a success after 0.5 sec above it
+    pub fn new() -> Self {
+        Self {
+            direction: RewardDirection::Above,
+            window: 30.0,
+            percentile: 0.7,
+            hold_time: 0.5,
+            baseline: VecDeque::new(),
+            rewarded_since: None,
+            last: None,
+            first_time: None,
+            reward_total: 0.0,
+            metric_total: 0.0,
+            threshold_total: 0.0,
+            threshold_count: 0,
+            stats: ProtocolStats::default(),
+        }
+    }
+
+    pub fn with_direction(mut self, direction: RewardDirection) -> Self {
+        self.direction = direction;
+
+        self
+    }
+
+    /// Set the threshold to the 'percentile' [0.0..1.0) of the metric over the last 'window' [sec]
+    ///
+    /// A higher percentile makes rewards rarer. With `RewardDirection::Below` the percentile counts down from the highest value, so 0.7 still means 30% of recent values earn a reward
+    ///
+    /// A negative or NaN 'window' is taken as 0.0, which keeps no baseline, and 'percentile' is clamped to [0.0..0.99]
+    pub fn with_baseline(mut self, window: f64, percentile: f32) -> Self {
+        self.window = window.max(0.0);
+        self.percentile = percentile.max(0.0).min(MAX_PERCENTILE);
+
+        self
+    }
+
+    /// [sec] How long the threshold must be beaten without a break for a success
+    ///
+    /// Holding on longer earns another success every 'hold_time'. A negative or NaN 'hold_time' is taken as 0.0
+    pub fn with_hold_time(mut self, hold_time: f64) -> Self {
+        self.hold_time = hold_time.max(0.0);
+
+        self
+    }
+
+    /// The metric value which must be beaten now, or None while the baseline is still being collected
+    pub fn threshold(&self) -> Option<f32> {
+        if self.baseline.len() < MIN_BASELINE_SAMPLES {
+            return None;
+        }
+        let mut values: Vec<f32> = self.baseline.iter().map(|s| s.value).collect();
+        values.sort_by(f32::total_cmp);
+        let percentile = match self.direction {
+            RewardDirection::Above => self.percentile,
+            RewardDirection::Below => 1.0 - self.percentile,
+        };
+        let index = (percentile * (values.len() - 1) as f32).round() as usize;
+
+        Some(values[index])
+    }
+
+    /// The response to the last sample pushed
+    pub fn last_reward(&self) -> Option<Reward> {
+        self.last
+    }
+
+    pub fn stats(&self) -> &ProtocolStats {
+        &self.stats
+    }
+
+    /// Add a metric sample, returning the reward it earns against the baseline before it
+    ///
+    /// A sample with a NaN or infinite time or value, such as a dropped sensor frame, is skipped. The baseline and statistics are unchanged and the last reward is repeated
+    pub fn push(&mut self, sample: Sample) -> Reward {
+        if !is_finite(sample) {
+            return Reward {
+                time: sample.time,
+                value: self.last.map_or(0.0, |r| r.value),
+                threshold: self.threshold(),
+                success: false,
+            };
+        }
+        let threshold = self.threshold();
+        let value = match threshold {
+            Some(_) => {
+                // How much of the baseline this value beats, ties counting half
+                let beaten: f32 = self
+                    .baseline
+                    .iter()
+                    .map(|s| match self.direction {
+                        _ if s.value == sample.value => 0.5,
+                        RewardDirection::Above if s.value < sample.value => 1.0,
+                        RewardDirection::Below if s.value > sample.value => 1.0,
+                        _ => 0.0,
+                    })
+                    .sum();
+                let rank = beaten / self.baseline.len() as f32;
+                ((rank - self.percentile) / (1.0 - self.percentile)).clamp(0.0, 1.0)
+            }
+            None => 0.0,
+        };
+
+        let mut success = false;
+        if value > 0.0 {
+            let since = *self.rewarded_since.get_or_insert(sample.time);
+            if sample.time - since >= self.hold_time {
+                success = true;
+                self.rewarded_since = Some(sample.time);
+            }
+        } else {
+            self.rewarded_since = None;
+        }
+
+        self.baseline.push_back(sample);
+        while self
+            .baseline
+            .front()
+            .map_or(false, |s| s.time < sample.time - self.window)
+        {
+            self.baseline.pop_front();
+        }
+
+        let reward = Reward {
+            time: sample.time,
+            value,
+            threshold,
+            success,
+        };
+        self.update_stats(sample, reward);
+        self.last = Some(reward);
+
+        reward
+    }
+
+    /// Add a metric sample and move the mandala to the reward through 'adapter', which smooths the reward and tunes the transition duration
+    ///
+    /// The transition starts at 'current_time' [sec] on the render clock, as for `SampleAdapter::apply()`
+    pub fn apply(
+        &mut self,
+        sample: Sample,
+        current_time: f64,
+        adapter: &mut SampleAdapter,
+        mandala: &mut Mandala,
+    ) -> Reward {
+        let reward = self.push(sample);
+        if is_finite(sample) {
+            adapter.apply(
+                Sample::new(sample.time, reward.value),
+                current_time,
+                mandala,
+            );
+        }
+
+        reward
+    }
+
+    /// Forget the baseline and statistics to start a new session
+    pub fn reset(&mut self) {
+        *self = Self {
+            direction: self.direction,
+            window: self.window,
+            percentile: self.percentile,
+            hold_time: self.hold_time,
+            ..Self::new()
+        };
+    }
+
+    fn update_stats(&mut self, sample: Sample, reward: Reward) {
+        // The time since the previous sample was rewarded if that sample was
+        if let Some(last) = self.last {
+            if last.value > 0.0 {
+                self.stats.rewarded_time += sample.time - last.time;
+            }
+        }
+        let first_time = *self.first_time.get_or_insert(sample.time);
+        self.reward_total += reward.value;
+        self.metric_total += sample.value;
+        if let Some(threshold) = reward.threshold {
+            self.threshold_total += threshold;
+            self.threshold_count += 1;
+        }
+
+        let stats = &mut self.stats;
+        stats.samples += 1;
+        stats.duration = sample.time - first_time;
+        stats.mean_reward = self.reward_total / stats.samples as f32;
+        stats.mean_metric = self.metric_total / stats.samples as f32;
+        if self.threshold_count > 0 {
+            stats.mean_threshold = Some(self.threshold_total / self.threshold_count as f32);
+        }
+        if reward.success {
+            stats.success_times.push(sample.time);
+        }
+    }
+}
+
+fn is_finite(sample: Sample) -> bool {
+    sample.time.is_finite() && sample.value.is_finite()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{RewardDirection, RewardProtocol};
+    use crate::timeline::Sample;
+
+    /// Push a baseline of 0.0, 0.1, .. 0.9, one sample every 0.1 sec
+    fn with_baseline(protocol: &mut RewardProtocol) {
+        for i in 0..10 {
+            let reward = protocol.push(Sample::new(i as f64 * 0.1, i as f32 * 0.1));
+            assert_eq!(None, reward.threshold);
+            assert_eq!(0.0, reward.value);
+        }
+    }
+
+    #[test]
+    fn test_reward_above_threshold() {
+        let mut protocol = RewardProtocol::new().with_baseline(10.0, 0.5);
+        with_baseline(&mut protocol);
+        assert_eq!(Some(0.5), protocol.threshold());
+
+        assert_eq!(0.0, protocol.push(Sample::new(1.0, 0.3)).value);
+        assert_eq!(1.0, protocol.push(Sample::new(1.1, 2.0)).value);
+        let reward = protocol.push(Sample::new(1.2, 0.75));
+        assert!(reward.value > 0.0 && reward.value < 1.0);
+    }
+
+    #[test]
+    fn test_percentile_is_limited_below_one() {
+        let mut protocol = RewardProtocol::new().with_baseline(10.0, 1.0);
+        with_baseline(&mut protocol);
+        assert_eq!(Some(9.0 * 0.1), protocol.threshold()); // The highest baseline value
+        assert!(protocol.push(Sample::new(1.0, 2.0)).value > 0.9);
+    }
+
+    #[test]
+    fn test_reward_below_threshold() {
+        let mut protocol = RewardProtocol::new()
+            .with_baseline(10.0, 0.5)
+            .with_direction(RewardDirection::Below);
+        with_baseline(&mut protocol);
+        assert_eq!(Some(0.5), protocol.threshold());
+
+        assert_eq!(0.0, protocol.push(Sample::new(1.0, 0.8)).value);
+        assert_eq!(1.0, protocol.push(Sample::new(1.1, -1.0)).value);
+    }
+
+    #[test]
+    fn test_non_finite_samples_are_skipped() {
+        let mut protocol = RewardProtocol::new().with_baseline(10.0, 0.5);
+        with_baseline(&mut protocol);
+        assert_eq!(1.0, protocol.push(Sample::new(1.0, 2.0)).value);
+        let reward = protocol.push(Sample::new(1.1, f32::NAN));
+        assert_eq!((1.0, false), (reward.value, reward.success));
+        protocol.push(Sample::new(f64::INFINITY, 0.5));
+        assert_eq!(Some(0.5), protocol.threshold());
+        assert_eq!(11, protocol.stats().samples);
+        assert_eq!(0.0, protocol.push(Sample::new(1.2, 0.1)).value);
+    }
+
+    #[test]
+    fn test_baseline_window_adapts() {
+        let mut protocol = RewardProtocol::new().with_baseline(1.0, 0.5);
+        with_baseline(&mut protocol);
+        for i in 10..30 {
+            protocol.push(Sample::new(i as f64 * 0.1, 5.0));
+        }
+        assert_eq!(Some(5.0), protocol.threshold()); // The early low values have left the window
+        assert_eq!(0.0, protocol.push(Sample::new(3.0, 4.0)).value);
+    }
+
+    #[test]
+    fn test_success_after_hold_time() {
+        let mut protocol = RewardProtocol::new()
+            .with_baseline(100.0, 0.5)
+            .with_hold_time(0.25);
+        with_baseline(&mut protocol);
+        let successes: Vec<bool> = (10..18)
+            .map(|i| protocol.push(Sample::new(i as f64 * 0.1, 10.0)).success)
+            .collect();
+        assert_eq!(
+            vec![false, false, false, true, false, false, true, false],
+            successes
+        );
+
+        // A break restarts the hold
+        assert!(!protocol.push(Sample::new(1.8, -1.0)).success);
+        assert!(!protocol.push(Sample::new(1.9, 10.0)).success);
+
+        let stats = protocol.stats();
+        assert_eq!(20, stats.samples);
+        assert_eq!(2, stats.success_times.len());
+        assert!((stats.success_times[1] - 1.6).abs() < 1e-9);
+        assert!((stats.rewarded_time - 0.8).abs() < 1e-9);
+        assert!((stats.duration - 1.9).abs() < 1e-9);
+
+        protocol.reset();
+        assert_eq!(None, protocol.threshold());
+        assert_eq!(0, protocol.stats().samples);
+    }
+}
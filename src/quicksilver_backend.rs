@@ -0,0 +1,64 @@
+//! Draw into quicksilver 0.3 meshes, enabled by the `quicksilver` cargo feature
+
+use crate::{
+    backend::{Backend, Vertex},
+    color::Color,
+    geom::Vector,
+};
+use quicksilver::graphics::{GpuTriangle, Mesh};
+
+impl Backend for Mesh {
+    fn draw_triangles(&mut self, vertices: &[Vertex], indices: &[u32]) {
+        let offset = self.vertices.len() as u32;
+        self.vertices
+            .extend(vertices.iter().map(|vertex| quicksilver::graphics::Vertex {
+                pos: vertex.position.into(),
+                tex_pos: None,
+                col: vertex.color.into(),
+            }));
+        self.triangles
+            .extend(indices.chunks(3).map(|triangle| GpuTriangle {
+                z: 0.0,
+                indices: [
+                    offset + triangle[0],
+                    offset + triangle[1],
+                    offset + triangle[2],
+                ],
+                image: None,
+            }));
+    }
+}
+
+impl From<Color> for quicksilver::graphics::Color {
+    fn from(color: Color) -> Self {
+        quicksilver::graphics::Color {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
+        }
+    }
+}
+
+impl From<quicksilver::graphics::Color> for Color {
+    fn from(color: quicksilver::graphics::Color) -> Self {
+        Color {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
+        }
+    }
+}
+
+impl From<Vector> for quicksilver::geom::Vector {
+    fn from(vector: Vector) -> Self {
+        quicksilver::geom::Vector::new(vector.x, vector.y)
+    }
+}
+
+impl From<quicksilver::geom::Vector> for Vector {
+    fn from(vector: quicksilver::geom::Vector) -> Self {
+        Vector::new(vector.x, vector.y)
+    }
+}
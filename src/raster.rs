@@ -0,0 +1,291 @@
+//! A software backend which draws into an in-memory RGBA image, for thumbnails, documentation and tests on machines without a GPU
+
+use crate::{
+    backend::{Backend, Vertex},
+    color::{interpolate_color, Color},
+    geom::Vector,
+};
+#[cfg(feature = "png")]
+use std::{fs::File, io, io::BufWriter, io::Write, path::Path};
+
+/// Sub-samples per pixel along each axis. 4x4 = 16 coverage samples fit one `u16` mask per pixel
+const SAMPLES: usize = 4;
+
+/// An RGBA image which shapes are rasterized into with anti-aliasing and alpha blending
+///
+/// All triangles of each shape are combined before blending, so the edges shared between triangles of one shape leave no visible seams. Each shape is filled with the color of its first vertex, which is how every shape in this crate is drawn
+#[derive(Clone, Debug)]
+pub struct RgbaCanvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+    coverage: Vec<u16>,
+}
+
+impl RgbaCanvas {
+    /// Create an image 'width' by 'height' pixels filled with 'background'
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        let pixel_count = width as usize * height as usize;
+
+        Self {
+            width,
+            height,
+            pixels: vec![background; pixel_count],
+            coverage: vec![0; pixel_count],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Fill the whole image with 'background', ready to draw the next frame
+    pub fn clear(&mut self, background: Color) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = background;
+        }
+    }
+
+    /// The color of the pixel at column 'x', row 'y'
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    /// The image as 8 bit RGBA values, row by row from the top left
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        let mut bytes = Vec::with_capacity(self.pixels.len() * 4);
+        for p in self.pixels.iter() {
+            bytes.extend_from_slice(&[to_byte(p.r), to_byte(p.g), to_byte(p.b), to_byte(p.a)]);
+        }
+
+        bytes
+    }
+
+    /// Encode the image as PNG
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba8())?;
+
+        Ok(())
+    }
+
+    /// Save the image as a PNG file
+    #[cfg(feature = "png")]
+    pub fn save_png(&self, file_name: impl AsRef<Path>) -> io::Result<()> {
+        self.write_png(BufWriter::new(File::create(file_name)?))
+    }
+
+    /// The offset of the pixel at column 'x', row 'y', computed in `usize` so large images do not overflow
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Set the coverage sample bits of every pixel inside the triangle 'a', 'b', 'c', and return the pixel bounds touched
+    fn cover_triangle(&mut self, a: Vector, b: Vector, c: Vector) -> Option<Bounds> {
+        let area = edge(a, b, c);
+        if area == 0.0 || !area.is_finite() {
+            return None;
+        }
+        let bounds = Bounds::of_triangle(a, b, c, self.width, self.height)?;
+
+        for y in bounds.min_y..bounds.max_y {
+            for x in bounds.min_x..bounds.max_x {
+                let mut mask = 0u16;
+                for sample in 0..SAMPLES * SAMPLES {
+                    let p = Vector {
+                        x: x as f32 + ((sample % SAMPLES) as f32 + 0.5) / SAMPLES as f32,
+                        y: y as f32 + ((sample / SAMPLES) as f32 + 0.5) / SAMPLES as f32,
+                    };
+                    // Inside when all three edges agree with the winding of the whole triangle
+                    let w0 = edge(b, c, p) * area;
+                    let w1 = edge(c, a, p) * area;
+                    let w2 = edge(a, b, p) * area;
+                    if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+                        mask |= 1 << sample;
+                    }
+                }
+                let i = self.index(x, y);
+                self.coverage[i] |= mask;
+            }
+        }
+
+        Some(bounds)
+    }
+
+    /// Blend 'color' into every pixel within 'bounds' in proportion to its coverage, then reset the coverage
+    fn fill_covered(&mut self, bounds: &Bounds, color: Color) {
+        for y in bounds.min_y..bounds.max_y {
+            for x in bounds.min_x..bounds.max_x {
+                let i = self.index(x, y);
+                let covered = self.coverage[i].count_ones();
+                if covered == 0 {
+                    continue;
+                }
+                self.coverage[i] = 0;
+
+                let alpha = color.a.clamp(0.0, 1.0) * covered as f32 / (SAMPLES * SAMPLES) as f32;
+                self.pixels[i] = blend_over(self.pixels[i], color, alpha);
+            }
+        }
+    }
+}
+
+impl Backend for RgbaCanvas {
+    fn draw_triangles(&mut self, vertices: &[Vertex], indices: &[u32]) {
+        let color = match vertices.first() {
+            Some(vertex) => vertex.color,
+            None => return,
+        };
+
+        let mut shape_bounds: Option<Bounds> = None;
+        for triangle in indices.chunks(3).filter(|t| t.len() == 3) {
+            let corner = |i: usize| vertices[triangle[i] as usize].position;
+            if let Some(bounds) = self.cover_triangle(corner(0), corner(1), corner(2)) {
+                shape_bounds = Some(match shape_bounds {
+                    Some(shape_bounds) => shape_bounds.union(&bounds),
+                    None => bounds,
+                });
+            }
+        }
+
+        if let Some(bounds) = shape_bounds {
+            self.fill_covered(&bounds, color);
+        }
+    }
+}
+
+/// The half-open pixel rectangle [min..max) touched by a shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bounds {
+    min_x: u32,
+    min_y: u32,
+    max_x: u32,
+    max_y: u32,
+}
+
+impl Bounds {
+    /// The pixels which may be touched by a triangle, clipped to the image, or None if it is entirely outside
+    fn of_triangle(a: Vector, b: Vector, c: Vector, width: u32, height: u32) -> Option<Self> {
+        let clip = |value: f32, max: u32| value.max(0.0).min(max as f32) as u32;
+        let min_x = clip(a.x.min(b.x).min(c.x).floor(), width);
+        let min_y = clip(a.y.min(b.y).min(c.y).floor(), height);
+        let max_x = clip(a.x.max(b.x).max(c.x).ceil(), width);
+        let max_y = clip(a.y.max(b.y).max(c.y).ceil(), height);
+        if min_x >= max_x || min_y >= max_y {
+            return None;
+        }
+
+        Some(Self {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        })
+    }
+
+    fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+}
+
+/// Twice the signed area of the triangle 'a', 'b', 'p', positive when 'p' is to the left of 'a' to 'b'
+fn edge(a: Vector, b: Vector, p: Vector) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Source-over blend of 'color' with opacity 'alpha' onto 'destination', using straight (not premultiplied) alpha
+fn blend_over(destination: Color, color: Color, alpha: f32) -> Color {
+    let a = alpha + destination.a * (1.0 - alpha);
+    if a <= 0.0 {
+        return Color {
+            a: 0.0,
+            ..destination
+        };
+    }
+    let source_weight = alpha / a;
+
+    Color {
+        a,
+        ..interpolate_color(&destination, &color, source_weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::{Backend, Vertex};
+    use crate::color::Color;
+    use crate::geom::Vector;
+    use crate::raster::RgbaCanvas;
+
+    fn square(canvas: &mut RgbaCanvas, x: f32, y: f32, size: f32, color: Color) {
+        let vertex = |dx: f32, dy: f32| Vertex {
+            position: Vector::new(x + dx, y + dy),
+            color,
+        };
+        canvas.draw_triangles(
+            &[
+                vertex(0.0, 0.0),
+                vertex(size, 0.0),
+                vertex(size, size),
+                vertex(0.0, size),
+            ],
+            &[0, 1, 2, 0, 2, 3],
+        );
+    }
+
+    #[test]
+    fn test_shared_triangle_edges_leave_no_seam() {
+        let mut canvas = RgbaCanvas::new(8, 8, Color::BLACK);
+        square(&mut canvas, 0.0, 0.0, 8.0, Color::RED);
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(Color::RED, canvas.pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_partial_coverage_is_anti_aliased() {
+        let mut canvas = RgbaCanvas::new(4, 4, Color::BLACK);
+        square(&mut canvas, 1.0, 1.0, 1.5, Color::WHITE);
+        assert_eq!(Color::WHITE, canvas.pixel(1, 1));
+        assert_eq!(Color::rgb(0.5, 0.5, 0.5), canvas.pixel(2, 1));
+        assert_eq!(Color::rgb(0.25, 0.25, 0.25), canvas.pixel(2, 2));
+        assert_eq!(Color::BLACK, canvas.pixel(3, 3));
+    }
+
+    #[test]
+    fn test_alpha_blending() {
+        let mut canvas = RgbaCanvas::new(2, 2, Color::BLACK);
+        square(&mut canvas, 0.0, 0.0, 2.0, Color::WHITE.with_alpha(0.5));
+        assert_eq!(Color::rgb(0.5, 0.5, 0.5), canvas.pixel(0, 0));
+        square(&mut canvas, 0.0, 0.0, 2.0, Color::WHITE.with_alpha(0.5));
+        assert_eq!(Color::rgb(0.75, 0.75, 0.75), canvas.pixel(0, 0));
+        assert_eq!(vec![191, 191, 191, 255], canvas.to_rgba8()[..4].to_vec());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_write_png() {
+        let mut png = Vec::new();
+        RgbaCanvas::new(2, 2, Color::RED)
+            .write_png(&mut png)
+            .unwrap();
+        assert_eq!(&[137, 80, 78, 71, 13, 10, 26, 10], &png[..8]);
+    }
+}
@@ -0,0 +1,216 @@
+//! Opt-in reloading of shapes and settings while the program runs, so a petal edited in Inkscape appears without a restart
+//!
+//! Files are polled for changes to their modification time or size, which needs no extra threads or platform file watching services
+
+#[cfg(feature = "config")]
+use crate::config::{ConfigError, MandalaConfig};
+use crate::{
+    mandala::{LayerKind, Mandala},
+    svg::ParseError,
+};
+use std::{error::Error, fmt, fs, time::SystemTime};
+
+/// Why a reload failed. The mandala is left as it was before
+#[derive(Debug)]
+pub enum ReloadError {
+    Svg(ParseError),
+    #[cfg(feature = "config")]
+    Config(ConfigError),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReloadError::Svg(e) => write!(f, "Reload failed: {}", e),
+            #[cfg(feature = "config")]
+            ReloadError::Config(e) => write!(f, "Reload failed: {}", e),
+        }
+    }
+}
+
+impl Error for ReloadError {}
+
+impl From<ParseError> for ReloadError {
+    fn from(e: ParseError) -> Self {
+        ReloadError::Svg(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<ConfigError> for ReloadError {
+    fn from(e: ConfigError) -> Self {
+        ReloadError::Config(e)
+    }
+}
+
+/// When a file was last seen to change, or None if it could not be read
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(file_name: &str) -> Stamp {
+    let metadata = fs::metadata(file_name).ok()?;
+
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Files to check for changes on disk
+#[derive(Clone, Debug, Default)]
+pub struct FileWatcher {
+    files: Vec<(String, Stamp)>,
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching a file. Only changes after this call are reported
+    pub fn watch(&mut self, file_name: &str) -> &mut Self {
+        if !self.files.iter().any(|(name, _)| name == file_name) {
+            self.files.push((file_name.to_string(), stamp(file_name)));
+        }
+
+        self
+    }
+
+    /// Stop watching all files
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// The files which have changed since the last call
+    ///
+    /// A file which is missing, for example while an editor replaces it, is reported once it is back
+    pub fn changed(&mut self) -> Vec<String> {
+        let mut changed = Vec::new();
+        for (file_name, last_stamp) in self.files.iter_mut() {
+            let stamp = stamp(file_name);
+            if stamp.is_some() && stamp != *last_stamp {
+                *last_stamp = stamp;
+                changed.push(file_name.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+/// Reload a mandala when the files it was created from change
+///
+/// Call `poll()` regularly, for example from each update. The animation value, its transition, any pause or freeze, any recording in progress, the observer and the caller's clock carry on across each reload
+#[derive(Clone, Debug, Default)]
+pub struct HotReload {
+    watcher: FileWatcher,
+    petal: Option<String>,
+    layers: Vec<(LayerKind, String)>,
+    #[cfg(feature = "config")]
+    config_file: Option<String>,
+}
+
+impl HotReload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reload the petal shape when this SVG file changes
+    pub fn watch_petal(&mut self, svg_file_name: &str) -> &mut Self {
+        self.watcher.watch(svg_file_name);
+        self.petal = Some(svg_file_name.to_string());
+
+        self
+    }
+
+    /// Reload the shape of a decorative layer when this SVG file changes
+    pub fn watch_layer(&mut self, kind: LayerKind, svg_file_name: &str) -> &mut Self {
+        self.watcher.watch(svg_file_name);
+        self.layers.retain(|(k, _)| *k != kind);
+        self.layers.push((kind, svg_file_name.to_string()));
+
+        self
+    }
+
+    /// Rebuild the whole mandala when the config file changes, and reload shapes when the SVG files named in 'config' change
+    #[cfg(feature = "config")]
+    pub fn watch_config(&mut self, config_file: &str, config: &MandalaConfig) -> &mut Self {
+        self.watcher.watch(config_file);
+        self.config_file = Some(config_file.to_string());
+        self.watch_config_shapes(config);
+
+        self
+    }
+
+    #[cfg(feature = "config")]
+    fn watch_config_shapes(&mut self, config: &MandalaConfig) {
+        self.layers.clear();
+        self.watch_petal(&config.petal);
+        for layer in config.layers.iter() {
+            self.watch_layer(layer.kind, &layer.svg);
+        }
+    }
+
+    /// Reload anything which has changed on disk into 'mandala'
+    ///
+    /// Returns true if anything was reloaded. On error the mandala is left as it was, and the next change to the file is tried again
+    pub fn poll(&mut self, mandala: &mut Mandala) -> Result<bool, ReloadError> {
+        let changed = self.watcher.changed();
+        if changed.is_empty() {
+            return Ok(false);
+        }
+
+        #[cfg(feature = "config")]
+        {
+            if let Some(config_file) = self.config_file.clone() {
+                if changed.contains(&config_file) {
+                    let config = MandalaConfig::load(&config_file)?;
+                    let mut rebuilt = config.build()?;
+                    rebuilt.restore_runtime_state(mandala.take_runtime_state());
+                    *mandala = rebuilt;
+                    self.watch_config_shapes(&config);
+
+                    return Ok(true);
+                }
+            }
+        }
+
+        let mut result = Ok(true);
+        let mut keep_first_error = |reloaded: Result<(), ParseError>| {
+            if let (Err(e), Ok(_)) = (reloaded, &result) {
+                result = Err(ReloadError::Svg(e));
+            }
+        };
+        if let Some(petal) = self.petal.as_ref().filter(|petal| changed.contains(petal)) {
+            keep_first_error(mandala.reload_petal(petal));
+        }
+        for (kind, svg_file_name) in self.layers.iter() {
+            if changed.contains(svg_file_name) {
+                if let Some(layer) = mandala.layer_mut(*kind) {
+                    keep_first_error(layer.reload(svg_file_name));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reload::FileWatcher;
+    use std::{env, fs};
+
+    #[test]
+    fn test_file_watcher() {
+        let file_name = env::temp_dir().join(format!("mandala-watch-{}.txt", std::process::id()));
+        let file_name = file_name.to_str().unwrap();
+        fs::write(file_name, "1").unwrap();
+
+        let mut watcher = FileWatcher::new();
+        watcher.watch(file_name);
+        assert!(watcher.changed().is_empty());
+        fs::write(file_name, "22").unwrap();
+        assert_eq!(vec![file_name.to_string()], watcher.changed());
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_file(file_name).unwrap();
+        assert!(watcher.changed().is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//! Several rings of petals sharing one hub
+
+use crate::mandala::{Mandala, MandalaState};
+use crate::{backend::Backend, geom::Vector, svg::ParseError};
+
+/// A single ring of a `ConcentricMandala`
+///
+/// Each ring has its own petal shape, count, radius, angular offset and open/closed states. A ring with a signal name is animated only by `ConcentricMandala::start_signal_transition()` for that signal, all others follow `ConcentricMandala::start_transition()`
+pub struct MandalaRing {
+    mandala: Mandala,
+    signal: Option<String>,
+}
+
+impl MandalaRing {
+    /// Create a ring of 'petal_count' petals placed 'radius' units out from the hub and turned by 'angular_offset' degrees
+    ///
+    /// Panics if the petal SVG can not be loaded
+    pub fn new(
+        petal_svg_filename: &str,
+        petal_count: usize,
+        radius: f32,
+        angular_offset: f32,
+        mandala_state_open: MandalaState,
+        mandala_state_closed: MandalaState,
+    ) -> Self {
+        Self::try_new(
+            petal_svg_filename,
+            petal_count,
+            radius,
+            angular_offset,
+            mandala_state_open,
+            mandala_state_closed,
+        )
+        .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a ring as `new()` does, or report why the petal SVG can not be loaded
+    pub fn try_new(
+        petal_svg_filename: &str,
+        petal_count: usize,
+        radius: f32,
+        angular_offset: f32,
+        mandala_state_open: MandalaState,
+        mandala_state_closed: MandalaState,
+    ) -> Result<Self, ParseError> {
+        let mut mandala = Mandala::try_new(
+            petal_svg_filename,
+            (0.0, 0.0),
+            (1.0, 1.0),
+            petal_count,
+            mandala_state_open,
+            mandala_state_closed,
+            0.0,
+        )?;
+        mandala.set_ring(radius, angular_offset);
+
+        Ok(Self {
+            mandala,
+            signal: None,
+        })
+    }
+
+    /// Drive this ring from its own named signal instead of the shared value
+    pub fn with_signal(mut self, signal: &str) -> Self {
+        self.signal = Some(signal.to_string());
+
+        self
+    }
+
+    /// The name of the signal driving this ring, or None if it follows the shared value
+    pub fn signal(&self) -> Option<&str> {
+        self.signal.as_deref()
+    }
+
+    /// Distance from the hub to the base of each petal in this ring
+    pub fn radius(&self) -> f32 {
+        self.mandala.ring_radius()
+    }
+
+    /// The single-ring mandala used to animate and draw this ring
+    pub fn mandala(&self) -> &Mandala {
+        &self.mandala
+    }
+
+    /// Mutable access to the single-ring mandala, for example to start a transition on just this ring
+    pub fn mandala_mut(&mut self) -> &mut Mandala {
+        &mut self.mandala
+    }
+}
+
+/// Several rings of petals sharing one hub, for layered lotus-like designs
+///
+/// Rings are kept sorted by radius and drawn inner-to-outer, so each ring is layered over the rings inside it regardless of the order they were added
+pub struct ConcentricMandala {
+    mandala_center: (Vector, Vector),
+    value: f32,
+    rings: Vec<MandalaRing>,
+}
+
+impl ConcentricMandala {
+    /// Create an empty set of rings centered at 'screen_position' and starting at 'value' [0.0-1.0]
+    pub fn new(screen_position: impl Into<Vector>, scale: impl Into<Vector>, value: f32) -> Self {
+        Self {
+            mandala_center: (screen_position.into(), scale.into()),
+            value,
+            rings: Vec::new(),
+        }
+    }
+
+    /// Add a ring, which takes the shared hub position and starts at the initial value
+    pub fn add_ring(&mut self, mut ring: MandalaRing) -> &mut Self {
+        let (screen_position, scale) = self.mandala_center;
+        ring.mandala
+            .set_center(screen_position, scale)
+            .set_value(self.value);
+        let index = self
+            .rings
+            .iter()
+            .position(|r| r.radius() > ring.radius())
+            .unwrap_or(self.rings.len());
+        self.rings.insert(index, ring);
+
+        self
+    }
+
+    /// Number of rings
+    pub fn ring_count(&self) -> usize {
+        self.rings.len()
+    }
+
+    /// The rings ordered inner-to-outer, as of the last `add_ring()` or `draw()`
+    pub fn rings(&self) -> &[MandalaRing] {
+        &self.rings
+    }
+
+    /// Mutable access to the rings ordered inner-to-outer
+    ///
+    /// A ring radius changed through `MandalaRing::mandala_mut()` moves the ring to its new place in the order at the next `draw()`
+    pub fn rings_mut(&mut self) -> &mut [MandalaRing] {
+        &mut self.rings
+    }
+
+    /// Start a transition on every ring which is not driven by its own signal
+    ///
+    /// See `Mandala::start_transition()` for guidance on choosing 'transition_duration'
+    pub fn start_transition(
+        &mut self,
+        current_time: f64,
+        transition_duration: f64,
+        target_value: f32,
+    ) {
+        for ring in self.rings.iter_mut().filter(|r| r.signal.is_none()) {
+            ring.mandala
+                .start_transition(current_time, transition_duration, target_value);
+        }
+    }
+
+    /// Start a transition on every ring driven by the named 'signal'
+    pub fn start_signal_transition(
+        &mut self,
+        signal: &str,
+        current_time: f64,
+        transition_duration: f64,
+        target_value: f32,
+    ) {
+        for ring in self
+            .rings
+            .iter_mut()
+            .filter(|r| r.signal.as_deref() == Some(signal))
+        {
+            ring.mandala
+                .start_transition(current_time, transition_duration, target_value);
+        }
+    }
+
+    /// Render every ring inner-to-outer to the backend
+    pub fn draw(&mut self, current_time: f64, backend: &mut dyn Backend) {
+        // Stable, so rings of equal radius keep the order they were added in
+        self.rings.sort_by(|a, b| a.radius().total_cmp(&b.radius()));
+        for ring in self.rings.iter_mut() {
+            ring.mandala.draw(current_time, backend);
+        }
+    }
+}
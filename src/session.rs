@@ -0,0 +1,734 @@
+//! Record what drove a mandala during a session and replay it exactly, to reproduce what a participant saw
+//!
+//! A recording is the mandala's transition when recording started followed by every `start_transition`, pause and freeze call and recorded signal sample in order. Sessions are saved as CSV to read and edit, or as a compact binary file
+//!
+//! Only those calls are replayed. `set_value()`, `try_set_value()`, `set_transition()`, `set_petal_count()` and `set_layout()` are not recorded, so avoid them while recording, or expect the replay to differ after them
+
+use crate::{
+    animation::{Easing, Transition},
+    input::SignalSample,
+    mandala::Mandala,
+    timeline::Sample,
+};
+use std::{error::Error, fmt, fs, io};
+
+const BINARY_MAGIC: &[u8] = b"MSES";
+const BINARY_VERSION: u8 = 1;
+const TAG_START: u8 = 0;
+const TAG_TRANSITION: u8 = 1;
+const TAG_SIGNAL_NAME: u8 = 2;
+const TAG_SIGNAL: u8 = 3;
+const TAG_PAUSE: u8 = 4;
+const TAG_RESUME: u8 = 5;
+const TAG_FREEZE: u8 = 6;
+const TAG_UNFREEZE: u8 = 7;
+
+/// Why a session could not be loaded or saved
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    /// The file could not be understood, with a description of where
+    Parse(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Io(e) => write!(f, "Can not access session: {}", e),
+            SessionError::Parse(message) => write!(f, "Can not parse session: {}", message),
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+impl From<io::Error> for SessionError {
+    fn from(e: io::Error) -> Self {
+        SessionError::Io(e)
+    }
+}
+
+fn parse_error(message: impl Into<String>) -> SessionError {
+    SessionError::Parse(message.into())
+}
+
+/// Something which happened during a session, in the order it happened
+#[derive(Clone, Debug, PartialEq)]
+pub enum SessionEvent {
+    /// A `Mandala::start_transition()` call and the easing in use at the time
+    Transition {
+        time: f64,
+        duration: f64,
+        value: f32,
+        easing: Easing,
+    },
+    /// An incoming sample, recorded with `Mandala::record_signal()`
+    Signal(SignalSample),
+    /// A `Mandala::pause()` call
+    Pause { time: f64 },
+    /// A `Mandala::resume()` call
+    Resume { time: f64 },
+    /// A `Mandala::freeze_at()` call
+    Freeze { time: f64, value: f32 },
+    /// A `Mandala::unfreeze()` call
+    Unfreeze { time: f64 },
+}
+
+impl SessionEvent {
+    /// [sec] When the event happened
+    pub fn time(&self) -> f64 {
+        match self {
+            SessionEvent::Transition { time, .. } => *time,
+            SessionEvent::Signal(signal) => signal.sample.time,
+            SessionEvent::Pause { time }
+            | SessionEvent::Resume { time }
+            | SessionEvent::Freeze { time, .. }
+            | SessionEvent::Unfreeze { time } => *time,
+        }
+    }
+}
+
+/// A recorded session
+#[derive(Clone, Debug, PartialEq)]
+pub struct Session {
+    start_time: f64,
+    initial: Transition,
+    events: Vec<SessionEvent>,
+}
+
+impl Session {
+    /// An empty recording starting at 'start_time' [sec] with the mandala following 'initial'
+    pub fn new(start_time: f64, initial: Transition) -> Self {
+        Self {
+            start_time,
+            initial,
+            events: Vec::new(),
+        }
+    }
+
+    /// [sec] When recording started
+    pub fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    /// [sec] When the last event happened, or the start time if there are none
+    pub fn end_time(&self) -> f64 {
+        self.events
+            .iter()
+            .map(SessionEvent::time)
+            .fold(self.start_time, f64::max)
+    }
+
+    /// The transition the mandala was following when recording started
+    pub fn initial(&self) -> Transition {
+        self.initial
+    }
+
+    pub fn events(&self) -> &[SessionEvent] {
+        &self.events
+    }
+
+    pub fn push(&mut self, event: SessionEvent) {
+        self.events.push(event);
+    }
+
+    /// Load a file, which is CSV if the name ends in `.csv` and binary otherwise
+    pub fn load(file_name: &str) -> Result<Self, SessionError> {
+        if file_name.ends_with(".csv") {
+            Self::from_csv_str(&fs::read_to_string(file_name)?)
+        } else {
+            Self::from_bytes(&fs::read(file_name)?)
+        }
+    }
+
+    /// Save to a file, which is CSV if the name ends in `.csv` and binary otherwise
+    pub fn save(&self, file_name: &str) -> Result<(), SessionError> {
+        if file_name.ends_with(".csv") {
+            fs::write(file_name, self.to_csv_string())?;
+        } else {
+            fs::write(file_name, self.to_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// One event per line after a `start` line:
+    ///
+    /// `start,<time>,<initial start time>,<duration>,<start value>,<end value>,<easing>`, `transition,<time>,<duration>,<value>,<easing>`, `signal,<time>,<value>,<name>`, `pause,<time>`, `resume,<time>`, `freeze,<time>,<value>` and `unfreeze,<time>`
+    ///
+    /// A signal name which starts with a quote, holds a line break or has a space at either end is written in double quotes, with `""` for a quote, `\n` and `\r` for line breaks and `\\` for a backslash
+    pub fn to_csv_string(&self) -> String {
+        let initial = &self.initial;
+        let mut csv = format!(
+            "# kind,time,...\nstart,{},{},{},{},{},{}\n",
+            self.start_time,
+            initial.start_time(),
+            initial.duration(),
+            initial.start_value(),
+            initial.end_value(),
+            initial.easing().name()
+        );
+        for event in &self.events {
+            csv += &match event {
+                SessionEvent::Transition {
+                    time,
+                    duration,
+                    value,
+                    easing,
+                } => format!(
+                    "transition,{},{},{},{}\n",
+                    time,
+                    duration,
+                    value,
+                    easing.name()
+                ),
+                SessionEvent::Signal(signal) => format!(
+                    "signal,{},{},{}\n",
+                    signal.sample.time,
+                    signal.sample.value,
+                    quote_name(&signal.signal)
+                ),
+                SessionEvent::Pause { time } => format!("pause,{}\n", time),
+                SessionEvent::Resume { time } => format!("resume,{}\n", time),
+                SessionEvent::Freeze { time, value } => format!("freeze,{},{}\n", time, value),
+                SessionEvent::Unfreeze { time } => format!("unfreeze,{}\n", time),
+            };
+        }
+
+        csv
+    }
+
+    /// Parse the format written by `to_csv_string()`. Blank lines and `#` comments are skipped
+    pub fn from_csv_str(csv: &str) -> Result<Self, SessionError> {
+        let mut session: Option<Session> = None;
+        for (line_number, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |what: &str| parse_error(format!("{} on line {}", what, line_number + 1));
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let time = |i: usize| -> Result<f64, SessionError> {
+                fields
+                    .get(i)
+                    .and_then(|f| f.parse().ok())
+                    .ok_or_else(|| error("not a time"))
+            };
+            let number = |i: usize| -> Result<f32, SessionError> {
+                fields
+                    .get(i)
+                    .and_then(|f| f.parse().ok())
+                    .ok_or_else(|| error("not a number"))
+            };
+            let easing = |i: usize| -> Result<Easing, SessionError> {
+                fields
+                    .get(i)
+                    .and_then(|f| Easing::from_name(f))
+                    .ok_or_else(|| error("unknown easing"))
+            };
+            match (fields[0], session.as_mut()) {
+                ("start", None) if fields.len() == 7 => {
+                    let initial = Transition::new(time(2)?, time(3)?, number(4)?, number(5)?)
+                        .with_easing(easing(6)?);
+                    session = Some(Session::new(time(1)?, initial));
+                }
+                ("transition", Some(session)) if fields.len() == 5 => {
+                    session.push(SessionEvent::Transition {
+                        time: time(1)?,
+                        duration: time(2)?,
+                        value: number(3)?,
+                        easing: easing(4)?,
+                    })
+                }
+                ("signal", Some(session)) if fields.len() >= 4 => {
+                    let name = line.splitn(4, ',').nth(3).unwrap_or_default(); // Names may contain commas
+                    let name = unquote_name(name).ok_or_else(|| error("badly quoted name"))?;
+                    let sample = Sample::new(time(1)?, number(2)?);
+                    session.push(SessionEvent::Signal(SignalSample::new(name, sample)));
+                }
+                ("pause", Some(session)) if fields.len() == 2 => {
+                    session.push(SessionEvent::Pause { time: time(1)? })
+                }
+                ("resume", Some(session)) if fields.len() == 2 => {
+                    session.push(SessionEvent::Resume { time: time(1)? })
+                }
+                ("freeze", Some(session)) if fields.len() == 3 => {
+                    session.push(SessionEvent::Freeze {
+                        time: time(1)?,
+                        value: number(2)?,
+                    })
+                }
+                ("unfreeze", Some(session)) if fields.len() == 2 => {
+                    session.push(SessionEvent::Unfreeze { time: time(1)? })
+                }
+                (_, None) => return Err(error("expected a 'start' line")),
+                _ => return Err(error("unexpected line")),
+            }
+        }
+
+        session.ok_or_else(|| parse_error("no 'start' line"))
+    }
+
+    /// Little-endian records after a 5 byte header, with each signal name stored once
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.push(BINARY_VERSION);
+        let initial = &self.initial;
+        bytes.push(TAG_START);
+        for time in &[self.start_time, initial.start_time(), initial.duration()] {
+            bytes.extend_from_slice(&time.to_le_bytes());
+        }
+        bytes.extend_from_slice(&initial.start_value().to_le_bytes());
+        bytes.extend_from_slice(&initial.end_value().to_le_bytes());
+        bytes.push(easing_code(initial.easing()));
+
+        let mut names: Vec<&str> = Vec::new();
+        for event in &self.events {
+            match event {
+                SessionEvent::Transition {
+                    time,
+                    duration,
+                    value,
+                    easing,
+                } => {
+                    bytes.push(TAG_TRANSITION);
+                    bytes.extend_from_slice(&time.to_le_bytes());
+                    bytes.extend_from_slice(&duration.to_le_bytes());
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                    bytes.push(easing_code(*easing));
+                }
+                SessionEvent::Signal(signal) => {
+                    let id = match names.iter().position(|n| *n == signal.signal) {
+                        Some(id) => id,
+                        None => {
+                            let name = signal.signal.as_bytes();
+                            bytes.push(TAG_SIGNAL_NAME);
+                            bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+                            bytes.extend_from_slice(name);
+                            names.push(&signal.signal);
+                            names.len() - 1
+                        }
+                    };
+                    bytes.push(TAG_SIGNAL);
+                    bytes.extend_from_slice(&(id as u32).to_le_bytes());
+                    bytes.extend_from_slice(&signal.sample.time.to_le_bytes());
+                    bytes.extend_from_slice(&signal.sample.value.to_le_bytes());
+                }
+                SessionEvent::Pause { time } => {
+                    bytes.push(TAG_PAUSE);
+                    bytes.extend_from_slice(&time.to_le_bytes());
+                }
+                SessionEvent::Resume { time } => {
+                    bytes.push(TAG_RESUME);
+                    bytes.extend_from_slice(&time.to_le_bytes());
+                }
+                SessionEvent::Freeze { time, value } => {
+                    bytes.push(TAG_FREEZE);
+                    bytes.extend_from_slice(&time.to_le_bytes());
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+                SessionEvent::Unfreeze { time } => {
+                    bytes.push(TAG_UNFREEZE);
+                    bytes.extend_from_slice(&time.to_le_bytes());
+                }
+            }
+        }
+
+        bytes
+    }
+
+    /// Parse the format written by `to_bytes()`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SessionError> {
+        if !bytes.starts_with(BINARY_MAGIC) {
+            return Err(parse_error("not a binary session file"));
+        }
+        let mut reader = Reader {
+            bytes,
+            position: BINARY_MAGIC.len(),
+        };
+        let version = reader.u8()?;
+        if version != BINARY_VERSION {
+            return Err(parse_error(format!("unsupported version {}", version)));
+        }
+        if reader.u8()? != TAG_START {
+            return Err(parse_error("missing start record"));
+        }
+        let start_time = reader.f64()?;
+        let initial = Transition::new(reader.f64()?, reader.f64()?, reader.f32()?, reader.f32()?)
+            .with_easing(reader.easing()?);
+        let mut session = Session::new(start_time, initial);
+
+        let mut names = Vec::new();
+        while reader.position < bytes.len() {
+            match reader.u8()? {
+                TAG_TRANSITION => session.push(SessionEvent::Transition {
+                    time: reader.f64()?,
+                    duration: reader.f64()?,
+                    value: reader.f32()?,
+                    easing: reader.easing()?,
+                }),
+                TAG_SIGNAL_NAME => {
+                    let length = reader.u32()? as usize;
+                    let name = String::from_utf8(reader.take(length)?.to_vec())
+                        .map_err(|_| parse_error("signal name is not UTF-8"))?;
+                    names.push(name);
+                }
+                TAG_SIGNAL => {
+                    let id = reader.u32()? as usize;
+                    let name = names
+                        .get(id)
+                        .ok_or_else(|| parse_error(format!("undefined signal {}", id)))?;
+                    let sample = Sample::new(reader.f64()?, reader.f32()?);
+                    session.push(SessionEvent::Signal(SignalSample::new(
+                        name.clone(),
+                        sample,
+                    )));
+                }
+                TAG_PAUSE => session.push(SessionEvent::Pause {
+                    time: reader.f64()?,
+                }),
+                TAG_RESUME => session.push(SessionEvent::Resume {
+                    time: reader.f64()?,
+                }),
+                TAG_FREEZE => session.push(SessionEvent::Freeze {
+                    time: reader.f64()?,
+                    value: reader.f32()?,
+                }),
+                TAG_UNFREEZE => session.push(SessionEvent::Unfreeze {
+                    time: reader.f64()?,
+                }),
+                tag => {
+                    return Err(parse_error(format!(
+                        "unknown record {} at byte {}",
+                        tag,
+                        reader.position - 1
+                    )))
+                }
+            }
+        }
+
+        Ok(session)
+    }
+}
+
+/// A signal name as written to CSV, quoted if reading it back as it is would change it
+fn quote_name(name: &str) -> String {
+    let plain = name.trim() == name
+        && !name.starts_with('"')
+        && !name.contains(|c: char| c == '\n' || c == '\r');
+    if plain {
+        return name.to_string();
+    }
+    let mut quoted = String::with_capacity(name.len() + 2);
+    quoted.push('"');
+    for c in name.chars() {
+        match c {
+            '"' => quoted.push_str("\"\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// The signal name written by `quote_name()`, or None if the quoting is broken
+fn unquote_name(text: &str) -> Option<String> {
+    let inner = match text.strip_prefix('"') {
+        Some(rest) => rest.strip_suffix('"')?,
+        None => return Some(text.to_string()),
+    };
+    let mut name = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        name.push(match c {
+            '"' => chars.next().filter(|next| *next == '"')?,
+            '\\' => match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                '\\' => '\\',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+
+    Some(name)
+}
+
+fn easing_code(easing: Easing) -> u8 {
+    Easing::ALL.iter().position(|e| *e == easing).unwrap_or(0) as u8
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], SessionError> {
+        let end = self.position + length;
+        if end > self.bytes.len() {
+            return Err(parse_error(format!(
+                "truncated at byte {}",
+                self.bytes.len()
+            )));
+        }
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, SessionError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SessionError> {
+        let mut number = [0; 4];
+        number.copy_from_slice(self.take(4)?);
+
+        Ok(u32::from_le_bytes(number))
+    }
+
+    fn f32(&mut self) -> Result<f32, SessionError> {
+        let mut number = [0; 4];
+        number.copy_from_slice(self.take(4)?);
+
+        Ok(f32::from_le_bytes(number))
+    }
+
+    fn f64(&mut self) -> Result<f64, SessionError> {
+        let mut number = [0; 8];
+        number.copy_from_slice(self.take(8)?);
+
+        Ok(f64::from_le_bytes(number))
+    }
+
+    fn easing(&mut self) -> Result<Easing, SessionError> {
+        let code = self.u8()?;
+        Easing::ALL
+            .get(code as usize)
+            .copied()
+            .ok_or_else(|| parse_error(format!("unknown easing {}", code)))
+    }
+}
+
+/// Feeds a recorded session back through a mandala, at any speed and from any point
+///
+/// Transitions are started exactly as recorded, so the mandala passes through the same values at the same session times
+#[derive(Clone, Debug)]
+pub struct SessionPlayer {
+    session: Session,
+    position: f64,
+    next: usize,
+    speed: f64,
+}
+
+impl SessionPlayer {
+    /// Ready to play from the start at normal speed. Call `seek()` or `advance()` to set up the mandala
+    pub fn new(session: Session) -> Self {
+        Self {
+            position: session.start_time(),
+            session,
+            next: 0,
+            speed: 1.0,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.set_speed(speed);
+
+        self
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// [sec] The session time reached, to draw the mandala at
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Session seconds played per second of real time, for example 4.0 to review a session quickly
+    ///
+    /// A negative or NaN 'speed' is taken as 0.0, which holds the replay still
+    pub fn set_speed(&mut self, speed: f64) -> &mut Self {
+        self.speed = speed.max(0.0);
+
+        self
+    }
+
+    /// True once every event has been replayed
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.session.events.len()
+    }
+
+    /// Jump to session 'time' [sec], replaying every transition before it from the start of the session so the mandala is exactly as it was at that time
+    pub fn seek(&mut self, mandala: &mut Mandala, time: f64) {
+        mandala.replay_from(self.session.initial);
+        self.next = 0;
+        self.position = time.max(self.session.start_time);
+        self.play_to(mandala);
+    }
+
+    /// Move forward by 'elapsed' [sec] of real time multiplied by the speed, returning the signal samples passed on the way
+    pub fn advance(&mut self, mandala: &mut Mandala, elapsed: f64) -> Vec<SignalSample> {
+        if self.next == 0 && self.position <= self.session.start_time {
+            mandala.replay_from(self.session.initial);
+        }
+        self.position += elapsed * self.speed;
+
+        self.play_to(mandala)
+    }
+
+    fn play_to(&mut self, mandala: &mut Mandala) -> Vec<SignalSample> {
+        let mut signals = Vec::new();
+        while let Some(event) = self.session.events.get(self.next) {
+            if event.time() > self.position {
+                break;
+            }
+            match event {
+                SessionEvent::Transition {
+                    time,
+                    duration,
+                    value,
+                    easing,
+                } => {
+                    // Each transition uses its recorded easing, then the mandala's own easing is put back
+                    let previous_easing = mandala.easing();
+                    mandala
+                        .set_easing(*easing)
+                        .start_transition(*time, *duration, *value);
+                    mandala.set_easing(previous_easing);
+                }
+                SessionEvent::Signal(signal) => signals.push(signal.clone()),
+                SessionEvent::Pause { time } => {
+                    mandala.pause(*time);
+                }
+                SessionEvent::Resume { time } => {
+                    mandala.resume(*time);
+                }
+                SessionEvent::Freeze { time, value } => {
+                    let _ = mandala.freeze_at(*time, *value);
+                }
+                SessionEvent::Unfreeze { time } => {
+                    mandala.unfreeze(*time);
+                }
+            }
+            self.next += 1;
+        }
+
+        signals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animation::{Easing, Transition};
+    use crate::input::SignalSample;
+    use crate::session::{Session, SessionEvent};
+    use crate::timeline::Sample;
+
+    fn session() -> Session {
+        let mut session = Session::new(1.0, Transition::new(0.0, 2.0, 0.0, 1.0));
+        session.push(SessionEvent::Signal(SignalSample::new(
+            "alpha",
+            Sample::new(1.5, 0.25),
+        )));
+        session.push(SessionEvent::Transition {
+            time: 1.5,
+            duration: 0.5,
+            value: 0.25,
+            easing: Easing::EaseInOut,
+        });
+        session.push(SessionEvent::Signal(SignalSample::new(
+            "alpha, left",
+            Sample::new(2.0, 0.75),
+        )));
+        session.push(SessionEvent::Signal(SignalSample::new(
+            "alpha",
+            Sample::new(2.5, 0.125),
+        )));
+        session.push(SessionEvent::Pause { time: 2.5 });
+        session.push(SessionEvent::Freeze {
+            time: 2.75,
+            value: 0.5,
+        });
+        session.push(SessionEvent::Unfreeze { time: 3.0 });
+        session.push(SessionEvent::Resume { time: 3.0 });
+
+        session
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let session = session();
+        let csv = session.to_csv_string();
+        assert!(csv.contains("\ntransition,1.5,0.5,0.25,ease_in_out\n"));
+        assert_eq!(session, Session::from_csv_str(&csv).unwrap());
+        assert!(csv.contains("\nfreeze,2.75,0.5\n"));
+        assert_eq!(3.0, session.end_time());
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let session = session();
+        let bytes = session.to_bytes();
+        assert_eq!(session, Session::from_bytes(&bytes).unwrap());
+        assert!(bytes.len() < session.to_csv_string().len());
+        assert!(Session::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Session::from_bytes(b"MSES\x09").is_err());
+    }
+
+    #[test]
+    fn test_binary_long_signal_name() {
+        let mut session = Session::new(0.0, Transition::fixed_value(0.5));
+        let name = "é".repeat(200);
+        for suffix in &["left", "right"] {
+            session.push(SessionEvent::Signal(SignalSample::new(
+                format!("{}{}", name, suffix),
+                Sample::new(1.0, 0.5),
+            )));
+        }
+        assert_eq!(session, Session::from_bytes(&session.to_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_csv_quoted_signal_names() {
+        let mut session = Session::new(0.0, Transition::fixed_value(0.5));
+        for name in &[
+            "\"quoted\", left",
+            "two\nlines\r\n",
+            " spaced ",
+            "back\\slash\"",
+            "",
+        ] {
+            session.push(SessionEvent::Signal(SignalSample::new(
+                *name,
+                Sample::new(1.0, 0.5),
+            )));
+        }
+        let csv = session.to_csv_string();
+        assert_eq!(7, csv.lines().count());
+        assert_eq!(session, Session::from_csv_str(&csv).unwrap());
+        assert!(Session::from_csv_str("start,0,0,1,0,0,linear\nsignal,1,1,\"open").is_err());
+        assert!(Session::from_csv_str("start,0,0,1,0,0,linear\nsignal,1,1,\"a\\t\"").is_err());
+    }
+
+    #[test]
+    fn test_invalid_csv() {
+        assert!(Session::from_csv_str("").is_err());
+        assert!(Session::from_csv_str("transition,1,1,1,linear").is_err());
+        assert!(Session::from_csv_str("start,0,0,1,0,0,wobbly").is_err());
+        assert!(Session::from_csv_str("start,0,0,1,0,0,linear\nsignal,x,1,a").is_err());
+    }
+}
@@ -0,0 +1,412 @@
+//! Streams of timestamped multichannel samples, such as EEG or heart rate, from any acquisition stack
+//!
+//! A `SignalSource` is pulled once per frame without blocking. Network sources use a text line protocol of `time,value1,value2,...` with one value per channel, time in seconds, blank lines and `#` comments ignored
+
+use crate::timeline::Sample;
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Read},
+    net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+};
+
+/// The largest IPv4 UDP payload
+const MAX_DATAGRAM_SIZE: usize = 65_507;
+const READ_BUFFER_SIZE: usize = 4096;
+/// The longest unfinished line a TCP source keeps, the same as the longest line a UDP datagram can hold
+const MAX_LINE_LENGTH: usize = MAX_DATAGRAM_SIZE;
+
+/// Why samples could not be pulled from a source
+#[derive(Debug)]
+pub enum SourceError {
+    Io(io::Error),
+    /// A line could not be understood, with a description of why
+    Parse(String),
+    /// The stream has no channel with this name
+    UnknownChannel(String),
+    /// The sender has gone and every sample it sent has been pulled
+    Disconnected,
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SourceError::Io(e) => write!(f, "Can not read signal: {}", e),
+            SourceError::Parse(message) => write!(f, "Can not parse signal: {}", message),
+            SourceError::UnknownChannel(name) => write!(f, "No signal channel '{}'", name),
+            SourceError::Disconnected => write!(f, "Signal source disconnected"),
+        }
+    }
+}
+
+impl Error for SourceError {}
+
+impl From<io::Error> for SourceError {
+    fn from(e: io::Error) -> Self {
+        SourceError::Io(e)
+    }
+}
+
+/// Describes a stream and its channels
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamInfo {
+    pub name: String,
+    /// One label per channel, such as electrode positions `TP9`, `AF7`, `AF8`, `TP10`
+    pub channels: Vec<String>,
+    /// [Hz] The expected sample rate, or None for irregular streams such as events
+    pub nominal_rate: Option<f32>,
+}
+
+impl StreamInfo {
+    pub fn new(name: impl Into<String>, channels: &[&str], nominal_rate: Option<f32>) -> Self {
+        Self {
+            name: name.into(),
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            nominal_rate,
+        }
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn channel_index(&self, channel: &str) -> Option<usize> {
+        self.channels.iter().position(|c| c == channel)
+    }
+}
+
+/// One reading of every channel at 'time' [sec], timestamped by the sender
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiSample {
+    pub time: f64,
+    pub values: Vec<f32>,
+}
+
+impl MultiSample {
+    pub fn new(time: f64, values: Vec<f32>) -> Self {
+        Self { time, values }
+    }
+
+    /// Parse a `time,value1,value2,...` line with 'channel_count' values, or None for a blank or comment line
+    ///
+    /// NaN and infinite numbers are an error, so a faulty sender can not disturb the mandala
+    pub fn parse_line(line: &str, channel_count: usize) -> Result<Option<Self>, SourceError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        let mut fields = line.split(',').map(str::trim);
+        let time = fields
+            .next()
+            .unwrap_or_default()
+            .parse::<f64>()
+            .ok()
+            .filter(|t| t.is_finite());
+        let values: Option<Vec<f32>> = fields
+            .map(|f| f.parse::<f32>().ok().filter(|v| v.is_finite()))
+            .collect();
+        match (time, values) {
+            (Some(time), Some(values)) if values.len() == channel_count => {
+                Ok(Some(Self::new(time, values)))
+            }
+            (Some(_), Some(values)) => Err(SourceError::Parse(format!(
+                "expected {} values but found {} in '{}'",
+                channel_count,
+                values.len(),
+                line
+            ))),
+            _ => Err(SourceError::Parse(format!(
+                "not a finite number in '{}'",
+                line
+            ))),
+        }
+    }
+}
+
+/// Pull timestamped multichannel samples as they arrive
+pub trait SignalSource {
+    fn info(&self) -> &StreamInfo;
+
+    /// Every sample which has arrived since the last pull, oldest first, without waiting
+    fn pull(&mut self) -> Result<Vec<MultiSample>, SourceError>;
+
+    /// Pull, keeping only the values of one channel
+    fn pull_channel(&mut self, channel: &str) -> Result<Vec<Sample>, SourceError> {
+        let index = self
+            .info()
+            .channel_index(channel)
+            .ok_or_else(|| SourceError::UnknownChannel(channel.to_string()))?;
+
+        Ok(self
+            .pull()?
+            .iter()
+            .filter_map(|s| s.values.get(index).map(|v| Sample::new(s.time, *v)))
+            .collect())
+    }
+}
+
+/// Samples from the complete lines in 'bytes', counting lines which can not be parsed
+fn parse_lines(
+    bytes: &[u8],
+    channel_count: usize,
+    samples: &mut Vec<MultiSample>,
+    rejected_lines: &mut usize,
+) {
+    for line in String::from_utf8_lossy(bytes).lines() {
+        match MultiSample::parse_line(line, channel_count) {
+            Ok(Some(sample)) => samples.push(sample),
+            Ok(None) => (),
+            Err(_) => *rejected_lines += 1,
+        }
+    }
+}
+
+/// Lines sent in UDP datagrams, each holding one or more complete lines
+#[derive(Debug)]
+pub struct UdpLineSource {
+    info: StreamInfo,
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+    rejected_lines: usize,
+}
+
+impl UdpLineSource {
+    /// Listen on an address such as `0.0.0.0:16571`
+    pub fn bind(address: impl ToSocketAddrs, info: StreamInfo) -> Result<Self, SourceError> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            info,
+            socket,
+            buffer: vec![0; MAX_DATAGRAM_SIZE],
+            rejected_lines: 0,
+        })
+    }
+
+    /// The address being listened on, with the port chosen if bound to port 0
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// How many received lines could not be parsed and were skipped
+    pub fn rejected_lines(&self) -> usize {
+        self.rejected_lines
+    }
+}
+
+impl SignalSource for UdpLineSource {
+    fn info(&self) -> &StreamInfo {
+        &self.info
+    }
+
+    fn pull(&mut self) -> Result<Vec<MultiSample>, SourceError> {
+        let mut samples = Vec::new();
+        loop {
+            match self.socket.recv(&mut self.buffer) {
+                Ok(length) => parse_lines(
+                    &self.buffer[..length],
+                    self.info.channel_count(),
+                    &mut samples,
+                    &mut self.rejected_lines,
+                ),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(samples)
+    }
+}
+
+/// Lines read from a TCP connection, which may be split anywhere between reads
+#[derive(Debug)]
+pub struct TcpLineSource {
+    info: StreamInfo,
+    stream: TcpStream,
+    partial: Vec<u8>, // Bytes after the last complete line
+    discarding: bool, // Skipping the rest of a line which grew too long
+    closed: bool,
+    rejected_lines: usize,
+}
+
+impl TcpLineSource {
+    /// Connect to a server which streams lines, such as `127.0.0.1:16572`
+    pub fn connect(address: impl ToSocketAddrs, info: StreamInfo) -> Result<Self, SourceError> {
+        Self::from_stream(TcpStream::connect(address)?, info)
+    }
+
+    /// Read from a connection which is already open, for example one accepted by a `TcpListener`
+    pub fn from_stream(stream: TcpStream, info: StreamInfo) -> Result<Self, SourceError> {
+        stream.set_nonblocking(true)?;
+
+        Ok(Self {
+            info,
+            stream,
+            partial: Vec::new(),
+            discarding: false,
+            closed: false,
+            rejected_lines: 0,
+        })
+    }
+
+    /// How many received lines could not be parsed or were too long, and were skipped
+    pub fn rejected_lines(&self) -> usize {
+        self.rejected_lines
+    }
+
+    /// Keep 'bytes' until their lines are complete. A line longer than `MAX_LINE_LENGTH` is dropped up to its next newline
+    fn receive(&mut self, mut bytes: &[u8]) {
+        if self.discarding {
+            match bytes.iter().position(|b| *b == b'\n') {
+                Some(i) => {
+                    self.discarding = false;
+                    bytes = &bytes[i + 1..];
+                }
+                None => return,
+            }
+        }
+        self.partial.extend_from_slice(bytes);
+
+        let line_start = self
+            .partial
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+        if self.partial.len() - line_start > MAX_LINE_LENGTH {
+            self.partial.truncate(line_start);
+            self.discarding = true;
+            self.rejected_lines += 1;
+        }
+    }
+}
+
+impl SignalSource for TcpLineSource {
+    fn info(&self) -> &StreamInfo {
+        &self.info
+    }
+
+    /// When the sender closes the connection, any unfinished last line is used and the next pull is `Disconnected`
+    fn pull(&mut self) -> Result<Vec<MultiSample>, SourceError> {
+        if self.closed {
+            return Err(SourceError::Disconnected);
+        }
+        let mut buffer = [0; READ_BUFFER_SIZE];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(length) => self.receive(&buffer[..length]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let complete = if self.closed {
+            self.partial.len()
+        } else {
+            self.partial
+                .iter()
+                .rposition(|b| *b == b'\n')
+                .map_or(0, |i| i + 1)
+        };
+        let mut samples = Vec::new();
+        parse_lines(
+            &self.partial[..complete],
+            self.info.channel_count(),
+            &mut samples,
+            &mut self.rejected_lines,
+        );
+        self.partial.drain(..complete);
+
+        Ok(samples)
+    }
+}
+
+/// Samples sent from another thread of the same program, or directly by a test
+#[derive(Debug)]
+pub struct ChannelSource {
+    info: StreamInfo,
+    receiver: Receiver<MultiSample>,
+}
+
+impl ChannelSource {
+    /// A source and the sender which feeds it. Clone the sender to feed it from several threads
+    pub fn new(info: StreamInfo) -> (Sender<MultiSample>, Self) {
+        let (sender, receiver) = mpsc::channel();
+
+        (sender, Self { info, receiver })
+    }
+}
+
+impl SignalSource for ChannelSource {
+    fn info(&self) -> &StreamInfo {
+        &self.info
+    }
+
+    /// Once every sender is dropped and every sample pulled, this is `Disconnected`
+    fn pull(&mut self) -> Result<Vec<MultiSample>, SourceError> {
+        let mut samples = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(sample) => samples.push(sample),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) if samples.is_empty() => {
+                    return Err(SourceError::Disconnected)
+                }
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+
+        Ok(samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::{ChannelSource, MultiSample, SignalSource, SourceError, StreamInfo};
+    use crate::timeline::Sample;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            Some(MultiSample::new(1.5, vec![0.25, -2.0])),
+            MultiSample::parse_line(" 1.5, 0.25 ,-2 ", 2).unwrap()
+        );
+        assert_eq!(None, MultiSample::parse_line("# time,a,b", 2).unwrap());
+        assert_eq!(None, MultiSample::parse_line("", 2).unwrap());
+        assert!(MultiSample::parse_line("1.5,0.25", 2).is_err());
+        assert!(MultiSample::parse_line("1.5,0.25,x", 2).is_err());
+        assert!(MultiSample::parse_line("1.5,0.25,nan", 2).is_err());
+        assert!(MultiSample::parse_line("inf,0.25,0.5", 2).is_err());
+        assert!(MultiSample::parse_line("1.5,-inf,0.5", 2).is_err());
+    }
+
+    #[test]
+    fn test_channel_source() {
+        let info = StreamInfo::new("heart", &["bpm", "hrv"], Some(1.0));
+        let (sender, mut source) = ChannelSource::new(info);
+        assert!(source.pull().unwrap().is_empty());
+
+        sender.send(MultiSample::new(0.0, vec![60.0, 0.1])).unwrap();
+        sender.send(MultiSample::new(1.0, vec![62.0, 0.2])).unwrap();
+        assert_eq!(
+            vec![Sample::new(0.0, 0.1), Sample::new(1.0, 0.2)],
+            source.pull_channel("hrv").unwrap()
+        );
+        match source.pull_channel("temperature") {
+            Err(SourceError::UnknownChannel(name)) => assert_eq!("temperature", name),
+            other => panic!("{:?}", other),
+        }
+
+        sender.send(MultiSample::new(2.0, vec![64.0, 0.3])).unwrap();
+        drop(sender);
+        assert_eq!(1, source.pull().unwrap().len());
+        assert!(matches!(source.pull(), Err(SourceError::Disconnected)));
+    }
+}
@@ -0,0 +1,166 @@
+//! Load the path from an SVG file as a vector shape
+
+use crate::{
+    color::Color,
+    geom::{Transform, Vector},
+};
+use lyon::{
+    path::{Path, PathEvent},
+    svg::path_utils::build_path,
+};
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs::File;
+use std::io::Read;
+
+/// Why an SVG file could not be loaded as a shape
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Load the first path in an SVG file, panicking if that fails
+pub fn svg_to_path(file_name: &str) -> Path {
+    load_path(file_name).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Load the first path in an SVG file
+pub fn load_path(file_name: &str) -> Result<Path, ParseError> {
+    let mut svg_str = String::new();
+    File::open(file_name)
+        .and_then(|mut file| file.read_to_string(&mut svg_str))
+        .map_err(|e| ParseError::new(format!("Can not open SVG file: '{}', {}", file_name, e)))?;
+
+    try_parse_path_from_svg_str(&svg_str)
+        .map_err(|e| ParseError::new(format!("SVG file '{}': {}", file_name, e)))
+}
+
+/// Build a path from the first path data found in the text of an SVG document, panicking if that fails
+pub fn parse_path_from_svg_str(svg_str: &str) -> Path {
+    try_parse_path_from_svg_str(svg_str).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Build a path from the first path data found in the text of an SVG document
+pub fn try_parse_path_from_svg_str(svg_str: &str) -> Result<Path, ParseError> {
+    let path_str = extract_path_str_from_svg_str(svg_str)
+        .ok_or_else(|| ParseError::new("Can not find path data in SVG file"))?;
+
+    build_path(Path::builder().with_svg(), &path_str)
+        .map_err(|e| ParseError::new(format!("Bad path data: {:?}", e)))
+}
+
+fn extract_path_str_from_svg_str(svg_str: &str) -> Option<String> {
+    let parser = ::svg::parser::Parser::new(svg_str);
+    for event in parser {
+        if let ::svg::parser::Event::Tag(_path, _type, attributes) = event {
+            if let Some(data) = attributes.get("d") {
+                return Some(data.to_string());
+            }
+        }
+    }
+
+    None
+}
+
+/// Write a path as SVG path data, the inverse of `parse_path_from_svg_str`
+pub fn path_to_svg_data(path: &Path) -> String {
+    let mut data = String::new();
+    for event in path.iter() {
+        match event {
+            PathEvent::Begin { at } => write!(data, "M {} {} ", at.x, at.y),
+            PathEvent::Line { to, .. } => write!(data, "L {} {} ", to.x, to.y),
+            PathEvent::Quadratic { ctrl, to, .. } => {
+                write!(data, "Q {} {} {} {} ", ctrl.x, ctrl.y, to.x, to.y)
+            }
+            PathEvent::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => write!(
+                data,
+                "C {} {} {} {} {} {} ",
+                ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y
+            ),
+            PathEvent::End { close: true, .. } => write!(data, "Z "),
+            PathEvent::End { close: false, .. } => Ok(()),
+        }
+        .unwrap();
+    }
+    data.truncate(data.trim_end().len());
+
+    data
+}
+
+/// A `<path>` element drawing 'path_data' with 'transform' and a solid 'color' fill
+pub(crate) fn path_element(path_data: &str, transform: &Transform, color: &Color) -> String {
+    let m = transform.to_array();
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    format!(
+        "<path d=\"{}\" transform=\"matrix({} {} {} {} {} {})\" fill=\"#{:02x}{:02x}{:02x}\" fill-opacity=\"{}\"/>",
+        path_data,
+        m[0][0],
+        m[1][0],
+        m[0][1],
+        m[1][1],
+        m[0][2],
+        m[1][2],
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        color.a.clamp(0.0, 1.0)
+    )
+}
+
+/// A standalone SVG document holding 'elements', sized to fit the 'bounds' (top left, bottom right) they cover
+pub(crate) fn svg_document(elements: &[String], bounds: Option<(Vector, Vector)>) -> String {
+    let (min, max) = bounds.unwrap_or((Vector::ZERO, Vector::ZERO));
+    let (width, height) = (max.x - min.x, max.y - min.y);
+    let mut document = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+        width, height, min.x, min.y, width, height
+    );
+    for element in elements {
+        document.push_str(element);
+        document.push('\n');
+    }
+    document.push_str("</svg>\n");
+
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::svg::{
+        load_path, parse_path_from_svg_str, path_to_svg_data, try_parse_path_from_svg_str,
+    };
+
+    #[test]
+    fn test_path_data_round_trip() {
+        let data = "M 0 0 L 10 0 Q 10 10 5 10 C 2 10 0 8 0 5 Z";
+        let path = parse_path_from_svg_str(&format!("<svg><path d=\"{}\"/></svg>", data));
+        assert_eq!(data, path_to_svg_data(&path));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(try_parse_path_from_svg_str("<svg><rect/></svg>").is_err());
+        assert!(try_parse_path_from_svg_str("<svg><path d=\"M 0 0 X\"/></svg>").is_err());
+        assert!(load_path("tests/missing.svg").is_err());
+        assert!(load_path("tests/petal.svg").is_ok());
+    }
+}
@@ -0,0 +1,233 @@
+//! A recorded series of values over time, to replay onto a mandala for offline rendering
+
+use crate::mandala::Mandala;
+use std::{error::Error, fmt, fs, io};
+
+/// One recorded value [0.0..1.0] at 'time' [sec] from the start of the session
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub time: f64,
+    pub value: f32,
+}
+
+impl Sample {
+    pub fn new(time: f64, value: f32) -> Self {
+        Self { time, value }
+    }
+}
+
+/// Why a timeline could not be loaded
+#[derive(Debug)]
+pub enum TimelineError {
+    Io(io::Error),
+    /// The text could not be understood, with a description of where
+    Parse(String),
+}
+
+impl fmt::Display for TimelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimelineError::Io(e) => write!(f, "Can not read timeline: {}", e),
+            TimelineError::Parse(message) => write!(f, "Can not parse timeline: {}", message),
+        }
+    }
+}
+
+impl Error for TimelineError {}
+
+impl From<io::Error> for TimelineError {
+    fn from(e: io::Error) -> Self {
+        TimelineError::Io(e)
+    }
+}
+
+/// Values in time order, each of which starts a transition when replayed just as a live sample would
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timeline {
+    samples: Vec<Sample>,
+}
+
+impl Timeline {
+    /// Create from samples in any order. Samples with a NaN or infinite time or value are dropped
+    pub fn new(mut samples: Vec<Sample>) -> Self {
+        samples.retain(|sample| sample.time.is_finite() && sample.value.is_finite());
+        samples.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Self { samples }
+    }
+
+    /// Load a file, which is JSON if the name ends in `.json` and CSV otherwise
+    pub fn load(file_name: &str) -> Result<Self, TimelineError> {
+        let text = fs::read_to_string(file_name)?;
+        if file_name.ends_with(".json") {
+            Self::from_json_str(&text)
+        } else {
+            Self::from_csv_str(&text)
+        }
+    }
+
+    /// Parse `time,value` lines. Blank lines, `#` comments and a header line are skipped
+    pub fn from_csv_str(csv: &str) -> Result<Self, TimelineError> {
+        let mut samples = Vec::new();
+        let mut header_skipped = false;
+        for (line_number, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let parsed = match fields.as_slice() {
+                [time, value] => time
+                    .parse::<f64>()
+                    .map_err(|e| e.to_string())
+                    .and_then(|time| {
+                        value
+                            .parse::<f32>()
+                            .map(|value| Sample::new(time, value))
+                            .map_err(|e| e.to_string())
+                    }),
+                _ => {
+                    return Err(TimelineError::Parse(format!(
+                        "line {}: expected 'time,value'",
+                        line_number + 1
+                    )))
+                }
+            };
+            match parsed {
+                Ok(sample) => samples.push(sample),
+                Err(_) if samples.is_empty() && !header_skipped => header_skipped = true,
+                Err(e) => {
+                    return Err(TimelineError::Parse(format!(
+                        "line {}: {}",
+                        line_number + 1,
+                        e
+                    )))
+                }
+            }
+        }
+
+        Self::checked(samples)
+    }
+
+    /// Parse a JSON array of `{"t": time, "value": value}` objects or `[time, value]` pairs
+    #[cfg(feature = "serde_json")]
+    pub fn from_json_str(json: &str) -> Result<Self, TimelineError> {
+        use serde_json::Value;
+
+        let parse_error = |e: &dyn fmt::Display| TimelineError::Parse(e.to_string());
+        let items = match serde_json::from_str::<Value>(json).map_err(|e| parse_error(&e))? {
+            Value::Array(items) => items,
+            _ => return Err(parse_error(&"expected an array of samples")),
+        };
+        let mut samples = Vec::with_capacity(items.len());
+        for (i, item) in items.iter().enumerate() {
+            let (time, value) = match item {
+                Value::Array(pair) if pair.len() == 2 => (pair[0].as_f64(), pair[1].as_f64()),
+                Value::Object(_) => (item["t"].as_f64(), item["value"].as_f64()),
+                _ => (None, None),
+            };
+            match (time, value) {
+                (Some(time), Some(value)) => samples.push(Sample::new(time, value as f32)),
+                _ => return Err(parse_error(&format!("sample {} has no time and value", i))),
+            }
+        }
+
+        Self::checked(samples)
+    }
+
+    /// JSON support needs the `serde_json` feature
+    #[cfg(not(feature = "serde_json"))]
+    pub fn from_json_str(_json: &str) -> Result<Self, TimelineError> {
+        Err(TimelineError::Parse(
+            "JSON timelines need the 'serde_json' feature".to_string(),
+        ))
+    }
+
+    fn checked(samples: Vec<Sample>) -> Result<Self, TimelineError> {
+        match samples
+            .iter()
+            .find(|s| !s.time.is_finite() || !s.value.is_finite())
+        {
+            Some(s) => Err(TimelineError::Parse(format!(
+                "sample at {} is not a finite number",
+                s.time
+            ))),
+            None => Ok(Self::new(samples)),
+        }
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Time of the last sample
+    pub fn duration(&self) -> f64 {
+        self.samples.last().map_or(0.0, |sample| sample.time)
+    }
+
+    /// Start a transition on 'mandala' for each sample after 'previous_time' up to and including 'current_time'
+    ///
+    /// Call once per frame with the time of the previous frame to replay the session as it was seen live
+    pub fn replay(
+        &self,
+        mandala: &mut Mandala,
+        previous_time: f64,
+        current_time: f64,
+        transition_duration: f64,
+    ) {
+        for sample in self
+            .samples
+            .iter()
+            .filter(|s| s.time > previous_time && s.time <= current_time)
+        {
+            mandala.start_transition(sample.time, transition_duration, sample.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timeline::{Sample, Timeline};
+
+    #[test]
+    fn test_parse_csv() {
+        let timeline =
+            Timeline::from_csv_str("time,value\n# comment\n1.5, 0.25\n\n0.5,1\n").unwrap();
+        assert_eq!(
+            &[Sample::new(0.5, 1.0), Sample::new(1.5, 0.25)],
+            timeline.samples()
+        );
+        assert_eq!(1.5, timeline.duration());
+        assert!(Timeline::from_csv_str("0.0,0.5\n1.0,x\n").is_err());
+        assert!(Timeline::from_csv_str("0.0,0.5,1.0\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_header_after_comment() {
+        let timeline =
+            Timeline::from_csv_str("# recorded session\n\ntime,value\n0.0,0.5\n").unwrap();
+        assert_eq!(&[Sample::new(0.0, 0.5)], timeline.samples());
+        assert!(Timeline::from_csv_str("# comment\ntime,value\nt,v\n0.0,0.5\n").is_err());
+    }
+
+    #[test]
+    fn test_non_finite_samples_are_dropped() {
+        let timeline = Timeline::new(vec![
+            Sample::new(f64::NAN, 0.5),
+            Sample::new(1.0, 0.25),
+            Sample::new(0.5, f32::INFINITY),
+        ]);
+        assert_eq!(&[Sample::new(1.0, 0.25)], timeline.samples());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_parse_json() {
+        let timeline = Timeline::from_json_str(r#"[{"t": 0.5, "value": 1}, [0.0, 0.5]]"#).unwrap();
+        assert_eq!(
+            &[Sample::new(0.0, 0.5), Sample::new(0.5, 1.0)],
+            timeline.samples()
+        );
+        assert!(Timeline::from_json_str(r#"{"t": 0.5}"#).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Live values from a WebSocket as JSON messages like `{"t":1.23,"signal":"focus","value":0.7}`
+//!
+//! Browser builds use the browser's own WebSocket. Native builds use a non-blocking `tungstenite` connection, so the same feed can be tested against a local server
+
+use crate::{input::SignalSample, timeline::Sample};
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+use std::{error::Error, fmt};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::{io, net::TcpStream};
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use stdweb::web::{
+    event::{IMessageEvent, SocketCloseEvent, SocketMessageEvent},
+    EventListenerHandle, IEventTarget, WebSocket,
+};
+
+/// Why values could not be received
+#[derive(Debug)]
+pub enum FeedError {
+    /// The connection could not be made or has failed, with a description of why
+    WebSocket(String),
+    /// A message is not the expected JSON, with a description of why
+    Parse(String),
+    /// The server closed the connection and every message it sent has been received
+    Disconnected,
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeedError::WebSocket(message) => write!(f, "WebSocket feed failed: {}", message),
+            FeedError::Parse(message) => write!(f, "Can not parse feed message: {}", message),
+            FeedError::Disconnected => write!(f, "WebSocket feed disconnected"),
+        }
+    }
+}
+
+impl Error for FeedError {}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl From<io::Error> for FeedError {
+    fn from(e: io::Error) -> Self {
+        FeedError::WebSocket(e.to_string())
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl From<tungstenite::Error> for FeedError {
+    fn from(e: tungstenite::Error) -> Self {
+        FeedError::WebSocket(e.to_string())
+    }
+}
+
+/// The JSON form of one value
+#[derive(Serialize, Deserialize)]
+struct FeedMessage {
+    t: f64,
+    signal: String,
+    value: f32,
+}
+
+/// Either one message or an array of messages sent together
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FeedBatch {
+    One(FeedMessage),
+    Many(Vec<FeedMessage>),
+}
+
+/// Parse the text of one WebSocket message, which is either a single value or an array of values
+pub fn parse_message(text: &str) -> Result<Vec<SignalSample>, FeedError> {
+    let batch = serde_json::from_str(text).map_err(|e| FeedError::Parse(e.to_string()))?;
+    let messages = match batch {
+        FeedBatch::One(message) => vec![message],
+        FeedBatch::Many(messages) => messages,
+    };
+
+    Ok(messages
+        .into_iter()
+        .map(|m| SignalSample::new(m.signal, Sample::new(m.t, m.value)))
+        .collect())
+}
+
+/// The JSON message a server sends for a sample
+pub fn to_message(sample: &SignalSample) -> String {
+    serde_json::to_string(&FeedMessage {
+        t: sample.sample.time,
+        signal: sample.signal.clone(),
+        value: sample.sample.value,
+    })
+    .expect("A feed message is always valid JSON")
+}
+
+/// Messages waiting to be polled, filled by browser event callbacks
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[derive(Debug, Default)]
+struct Inbox {
+    messages: VecDeque<String>,
+    closed: bool,
+}
+
+/// A WebSocket connection polled once per frame for the values which have arrived
+pub struct WebSocketFeed {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    socket: tungstenite::WebSocket<TcpStream>,
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    socket: WebSocket,
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    inbox: Rc<RefCell<Inbox>>,
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    listeners: Vec<EventListenerHandle>,
+    closed: bool,
+    rejected_messages: usize,
+}
+
+impl fmt::Debug for WebSocketFeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WebSocketFeed")
+            .field("closed", &self.closed)
+            .field("rejected_messages", &self.rejected_messages)
+            .finish()
+    }
+}
+
+impl WebSocketFeed {
+    /// Connect to a `ws://host:port/path` URL, waiting for the handshake to complete
+    ///
+    /// Native builds support plain `ws://` only. Put a TLS proxy in front of a `wss://` server
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn connect(url: &str) -> Result<Self, FeedError> {
+        let stream = TcpStream::connect(socket_address(url)?)?;
+        let (socket, _) =
+            tungstenite::client(url, stream).map_err(|e| FeedError::WebSocket(e.to_string()))?;
+        socket.get_ref().set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            closed: false,
+            rejected_messages: 0,
+        })
+    }
+
+    /// Start connecting to a `ws://` or `wss://` URL. Values arrive once the browser has opened the connection
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    pub fn connect(url: &str) -> Result<Self, FeedError> {
+        let socket = WebSocket::new(url).map_err(|e| FeedError::WebSocket(format!("{:?}", e)))?;
+        let inbox = Rc::new(RefCell::new(Inbox::default()));
+        let message_inbox = inbox.clone();
+        let close_inbox = inbox.clone();
+        let listeners = vec![
+            socket.add_event_listener(move |event: SocketMessageEvent| {
+                if let Some(text) = event.data().into_text() {
+                    message_inbox.borrow_mut().messages.push_back(text);
+                }
+            }),
+            socket.add_event_listener(move |_: SocketCloseEvent| {
+                close_inbox.borrow_mut().closed = true;
+            }),
+        ];
+
+        Ok(Self {
+            socket,
+            inbox,
+            listeners,
+            closed: false,
+            rejected_messages: 0,
+        })
+    }
+
+    /// How many messages could not be parsed and were skipped
+    pub fn rejected_messages(&self) -> usize {
+        self.rejected_messages
+    }
+
+    /// Every value which has arrived since the last poll, in the order sent, timestamped by the sender
+    ///
+    /// Messages which are not valid JSON values are skipped and counted in `rejected_messages()`
+    pub fn poll(&mut self) -> Result<Vec<SignalSample>, FeedError> {
+        if self.closed {
+            return Err(FeedError::Disconnected);
+        }
+        let mut samples = Vec::new();
+        for text in self.receive()? {
+            match parse_message(&text) {
+                Ok(parsed) => samples.extend(parsed),
+                Err(_) => self.rejected_messages += 1,
+            }
+        }
+
+        Ok(samples)
+    }
+
+    /// The text messages which have arrived, noting when the connection closes
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn receive(&mut self) -> Result<Vec<String>, FeedError> {
+        let mut texts = Vec::new();
+        loop {
+            match self.socket.read_message() {
+                Ok(tungstenite::Message::Text(text)) => texts.push(text),
+                Ok(tungstenite::Message::Close(_)) => {
+                    let _ = self.socket.write_pending(); // Best effort reply to complete the close handshake
+                    self.closed = true;
+                    break;
+                }
+                Ok(_) => (), // Pings are answered automatically, binary messages are not values
+                Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(tungstenite::Error::ConnectionClosed)
+                | Err(tungstenite::Error::AlreadyClosed) => {
+                    self.closed = true;
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(texts)
+    }
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    fn receive(&mut self) -> Result<Vec<String>, FeedError> {
+        let mut inbox = self.inbox.borrow_mut();
+        self.closed = inbox.closed;
+
+        Ok(inbox.messages.drain(..).collect())
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+impl Drop for WebSocketFeed {
+    fn drop(&mut self) {
+        for listener in self.listeners.drain(..) {
+            listener.remove();
+        }
+        self.socket.close();
+    }
+}
+
+/// The `host:port` to open a TCP connection to for a `ws://` URL
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn socket_address(url: &str) -> Result<String, FeedError> {
+    let rest = url.strip_prefix("ws://").ok_or_else(|| {
+        FeedError::WebSocket(format!("Only ws:// URLs are supported, not '{}'", url))
+    })?;
+    let host = rest.split('/').next().unwrap_or(rest);
+    if host.ends_with(']') || !host.contains(':') {
+        Ok(format!("{}:80", host))
+    } else {
+        Ok(host.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::SignalSample;
+    use crate::timeline::Sample;
+    use crate::websocket::{parse_message, to_message};
+
+    #[test]
+    fn test_parse_message() {
+        let focus = SignalSample::new("focus", Sample::new(1.25, 0.75));
+        assert_eq!(
+            vec![focus.clone()],
+            parse_message(r#"{"t":1.25,"signal":"focus","value":0.75}"#).unwrap()
+        );
+        assert_eq!(
+            vec![focus.clone(), focus.clone()],
+            parse_message(&format!("[{0},{0}]", to_message(&focus))).unwrap()
+        );
+        assert!(parse_message(r#"{"signal":"focus","value":0.75}"#).is_err());
+        assert!(parse_message("0.75").is_err());
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    #[test]
+    fn test_socket_address() {
+        use crate::websocket::socket_address;
+
+        assert_eq!(
+            "localhost:9001",
+            socket_address("ws://localhost:9001/values").unwrap()
+        );
+        assert_eq!(
+            "example.com:80",
+            socket_address("ws://example.com").unwrap()
+        );
+        assert!(socket_address("wss://example.com").is_err());
+    }
+}
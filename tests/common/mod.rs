@@ -0,0 +1,26 @@
+//! Fixtures shared by the integration tests
+
+use mandala_quicksilver::{Color, Mandala, MandalaState, Transform};
+
+/// Five test petals around (500, 500), red and turned out when open, yellow and narrow when closed, starting at 'value'
+pub fn test_mandala(value: f32) -> Mandala {
+    Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        MandalaState::new(
+            Color::RED,
+            Transform::rotate(90),
+            Transform::translate((50.0, 0.0)),
+            Transform::scale((1.0, 1.0)),
+        ),
+        MandalaState::new(
+            Color::YELLOW,
+            Transform::rotate(0.0),
+            Transform::translate((0.0, 0.0)),
+            Transform::scale((0.1, 1.0)),
+        ),
+        value,
+    )
+}
@@ -0,0 +1,84 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{
+    Calibration, Color, Mandala, MandalaState, Sample, SampleAdapter, SignalRange, Transform,
+};
+
+/// A participant's heart rate [bpm], resting around 70 with one artifact spike
+fn heart_rate(i: usize) -> f32 {
+    if i == 7 {
+        250.0
+    } else {
+        70.0 + (i as f32 * 0.9).sin() * 10.0
+    }
+}
+
+fn calibrate() -> SignalRange {
+    let mut calibration = Calibration::new(30.0);
+    let mut i = 0;
+    while !calibration.push(Sample::new(i as f64 * 0.25, heart_rate(i))) {
+        i += 1;
+    }
+    assert_eq!(1.0, calibration.progress());
+
+    calibration.range().unwrap()
+}
+
+#[test]
+fn integration_test_calibration() {
+    let range = calibrate();
+    assert_eq!(121, range.samples);
+    assert_eq!(250.0, range.max);
+    assert!(range.high < 81.0); // The spike does not stretch the robust range
+    assert!(range.low > 59.0);
+
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        MandalaState::new(
+            Color::RED,
+            Transform::rotate(90),
+            Transform::translate((50.0, 0.0)),
+            Transform::scale((1.0, 1.0)),
+        ),
+        MandalaState::new(
+            Color::YELLOW,
+            Transform::rotate(0.0),
+            Transform::translate((0.0, 0.0)),
+            Transform::scale((0.1, 1.0)),
+        ),
+        0.0,
+    );
+    let normalizer = range.normalizer();
+    let mut adapter = SampleAdapter::new();
+    for (i, bpm) in [60.0, 70.0, 200.0].iter().enumerate() {
+        let time = 30.0 + i as f64;
+        let sample = normalizer
+            .normalize_sample(Sample::new(time, *bpm))
+            .unwrap();
+        adapter.apply(sample, time, &mut mandala);
+        let value = mandala.current_value(time + 1.0);
+        assert!((0.0..=1.0).contains(&value));
+        if i == 1 {
+            assert!((value - 0.5).abs() < 0.1);
+        }
+    }
+    assert_eq!(1.0, mandala.current_value(40.0));
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn integration_test_calibration_saved() {
+    let range = calibrate();
+    let file_name = std::env::temp_dir()
+        .join(format!("mandala-calibration-{}.json", std::process::id()))
+        .to_string_lossy()
+        .into_owned();
+    range.save(&file_name).unwrap();
+    let loaded = SignalRange::load(&file_name).unwrap();
+    std::fs::remove_file(&file_name).unwrap();
+    assert_eq!(range, loaded);
+    assert_eq!(range.normalizer(), loaded.normalizer());
+}
@@ -0,0 +1,87 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{
+    Color, ConcentricMandala, MandalaRing, MandalaState, MeshBuffer, Transform,
+};
+
+fn open_state() -> MandalaState {
+    MandalaState::new(
+        Color::RED,
+        Transform::rotate(90),
+        Transform::translate((50.0, 0.0)),
+        Transform::scale((1.0, 1.0)),
+    )
+}
+
+fn closed_state() -> MandalaState {
+    MandalaState::new(
+        Color::YELLOW,
+        Transform::rotate(0.0),
+        Transform::translate((0.0, 0.0)),
+        Transform::scale((0.1, 1.0)),
+    )
+}
+
+#[test]
+fn integration_test_concentric_mandala() {
+    let mut mandala = ConcentricMandala::new((500, 500), (2, 2), 0.0);
+    mandala
+        .add_ring(MandalaRing::new(
+            "tests/petal.svg",
+            8,
+            100.0,
+            22.5,
+            open_state(),
+            closed_state(),
+        ))
+        .add_ring(
+            MandalaRing::new("tests/petal.svg", 5, 0.0, 0.0, open_state(), closed_state())
+                .with_signal("alpha"),
+        );
+
+    // Rings are ordered inner-to-outer regardless of insertion order
+    assert_eq!(2, mandala.ring_count());
+    assert_eq!(0.0, mandala.rings()[0].radius());
+    assert_eq!(100.0, mandala.rings()[1].radius());
+    assert_eq!(Some("alpha"), mandala.rings()[0].signal());
+
+    mandala.start_transition(0.0, 2.0, 1.0);
+    mandala.start_signal_transition("alpha", 0.0, 4.0, 0.5);
+    assert_eq!(0.5, mandala.rings()[1].mandala().current_value(1.0));
+    assert_eq!(0.125, mandala.rings()[0].mandala().current_value(1.0));
+
+    let mut mesh = MeshBuffer::new();
+    mandala.draw(1.0, &mut mesh);
+    let expected = 179 * 13; // Same petal in both rings
+    assert_eq!(expected, mesh.triangle_count());
+}
+
+#[test]
+fn integration_test_concentric_mandala_reorders_rings() {
+    assert!(MandalaRing::try_new(
+        "tests/missing.svg",
+        5,
+        0.0,
+        0.0,
+        open_state(),
+        closed_state()
+    )
+    .is_err());
+
+    let mut mandala = ConcentricMandala::new((500, 500), (2, 2), 0.0);
+    for radius in &[0.0, 100.0] {
+        mandala.add_ring(MandalaRing::new(
+            "tests/petal.svg",
+            5,
+            *radius,
+            0.0,
+            open_state(),
+            closed_state(),
+        ));
+    }
+    mandala.rings_mut()[0].mandala_mut().set_ring(200.0, 0.0);
+
+    mandala.draw(0.0, &mut MeshBuffer::new());
+    assert_eq!(100.0, mandala.rings()[0].radius());
+    assert_eq!(200.0, mandala.rings()[1].radius());
+}
@@ -1,10 +1,6 @@
 extern crate mandala_quicksilver;
 
-use mandala_quicksilver::{Mandala, MandalaState};
-use quicksilver::{
-    geom::Transform,
-    graphics::{Color, Mesh, ShapeRenderer},
-};
+use mandala_quicksilver::{Color, Mandala, MandalaState, MeshBuffer, Transform};
 
 #[test]
 fn integration_test_draw_mandala_closed() {
@@ -31,11 +27,10 @@ fn integration_test_draw_mandala_closed() {
     );
     assert_eq!(0.5, mandala.current_value(0.0));
 
-    let mut mesh = Mesh::new();
-    let mut shape_renderer = ShapeRenderer::new(&mut mesh, Color::PURPLE);
+    let mut mesh = MeshBuffer::new();
     let seconds_since_start = 0.1;
 
-    mandala.draw(seconds_since_start, &mut shape_renderer);
-    let expected = 660; //TODO Is this correct?
-    assert_eq!(expected, (&mesh.triangles).len());
+    mandala.draw(seconds_since_start, &mut mesh);
+    let expected = 5 * 179; // 179 triangles per petal from the lyon 0.17 tessellator
+    assert_eq!(expected, mesh.triangle_count());
 }
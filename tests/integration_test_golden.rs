@@ -0,0 +1,220 @@
+//! Render mandalas to images and compare them with the reference PNGs in tests/golden
+//!
+//! A missing reference is created from the current output. Run with `UPDATE_GOLDEN=1` to replace all references after an intended change to how mandalas look, and review the new images before committing them.
+//! On a mismatch the rendered image and a diff, with differing pixels in red, are written next to the test binaries in the target directory
+#![cfg(feature = "png")]
+
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{
+    Color, ConcentricMandala, LayerKind, LayerState, Mandala, MandalaLayer, MandalaRing,
+    MandalaState, PetalDrawOrder, PetalLayout, PetalOverlap, Preset, RgbaCanvas, Transform,
+};
+use std::{env, fs, fs::File, io::BufReader, path::PathBuf};
+
+const SIZE: u32 = 128;
+const COLOR_BACKGROUND: Color = Color::BLACK;
+/// Largest perceptual color distance [0.0..1.0] between two pixels which are still considered the same
+const PIXEL_TOLERANCE: f32 = 0.05;
+/// Fraction of the image which may differ by more than PIXEL_TOLERANCE, for small edge anti-aliasing changes
+const AREA_TOLERANCE: f32 = 0.005;
+
+fn mandala_state_open() -> MandalaState {
+    MandalaState::new(
+        Color::RED,
+        Transform::rotate(90),
+        Transform::translate((50.0, 0.0)),
+        Transform::scale((1.0, 1.0)),
+    )
+}
+
+fn mandala_state_closed() -> MandalaState {
+    MandalaState::new(
+        Color::YELLOW.with_alpha(0.5),
+        Transform::rotate(0.0),
+        Transform::translate((0.0, 0.0)),
+        Transform::scale((0.1, 1.0)),
+    )
+}
+
+fn mandala(petal_count: usize, value: f32) -> Mandala {
+    Mandala::new(
+        "tests/petal.svg",
+        (SIZE as f32 / 2.0, SIZE as f32 / 2.0),
+        (0.2, 0.2),
+        petal_count,
+        mandala_state_open(),
+        mandala_state_closed(),
+        value,
+    )
+}
+
+fn render(draw: impl FnOnce(&mut RgbaCanvas)) -> RgbaCanvas {
+    let mut canvas = RgbaCanvas::new(SIZE, SIZE, COLOR_BACKGROUND);
+    draw(&mut canvas);
+
+    canvas
+}
+
+/// Distance between two 8 bit RGBA pixels, weighted for how the eye sees each channel, from 0.0 same to 1.0 black vs. white
+fn perceptual_distance(a: &[u8], b: &[u8]) -> f32 {
+    // Compare as if over a black background, so fully transparent pixels of any color match
+    let channel = |pixel: &[u8], i: usize| pixel[i] as f32 * pixel[3] as f32 / (255.0 * 255.0);
+    let red_mean = (channel(a, 0) + channel(b, 0)) / 2.0;
+    let (dr, dg, db) = (
+        channel(a, 0) - channel(b, 0),
+        channel(a, 1) - channel(b, 1),
+        channel(a, 2) - channel(b, 2),
+    );
+    let distance_squared = (2.0 + red_mean) * dr * dr + 4.0 * dg * dg + (3.0 - red_mean) * db * db;
+
+    (distance_squared / 9.0).sqrt()
+}
+
+fn read_png(path: &PathBuf) -> (u32, u32, Vec<u8>) {
+    let (info, mut reader) = png::Decoder::new(BufReader::new(File::open(path).unwrap()))
+        .read_info()
+        .unwrap();
+    assert_eq!(png::ColorType::RGBA, info.color_type, "{:?}", path);
+    let mut pixels = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+
+    (info.width, info.height, pixels)
+}
+
+fn write_png(path: &PathBuf, width: u32, height: u32, pixels: &[u8]) {
+    let mut encoder = png::Encoder::new(File::create(path).unwrap(), width, height);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(pixels)
+        .unwrap();
+}
+
+/// Compare with the reference image 'name', or create the reference if there is none yet
+fn assert_golden(name: &str, canvas: &RgbaCanvas) {
+    let reference = PathBuf::from("tests/golden").join(format!("{}.png", name));
+    if env::var_os("UPDATE_GOLDEN").is_some() || !reference.exists() {
+        fs::create_dir_all(reference.parent().unwrap()).unwrap();
+        canvas.save_png(&reference).unwrap();
+        eprintln!("Wrote reference image {:?}", reference);
+        return;
+    }
+
+    let actual = canvas.to_rgba8();
+    let (width, height, expected) = read_png(&reference);
+    assert_eq!(
+        (canvas.width(), canvas.height()),
+        (width, height),
+        "{} size",
+        name
+    );
+
+    let mut diff = Vec::with_capacity(expected.len());
+    let mut different_pixels = 0;
+    for (a, b) in actual.chunks(4).zip(expected.chunks(4)) {
+        if perceptual_distance(a, b) > PIXEL_TOLERANCE {
+            different_pixels += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = (b[0] as u32 + b[1] as u32 + b[2] as u32) / 9; // Faded reference for context
+            diff.extend_from_slice(&[gray as u8, gray as u8, gray as u8, 255]);
+        }
+    }
+
+    let different_fraction = different_pixels as f32 / (width * height) as f32;
+    if different_fraction > AREA_TOLERANCE {
+        let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+        let actual_path = out.join(format!("{}.actual.png", name));
+        let diff_path = out.join(format!("{}.diff.png", name));
+        canvas.save_png(&actual_path).unwrap();
+        write_png(&diff_path, width, height, &diff);
+        panic!(
+            "{}: {} of {} pixels differ from {:?}, see {:?} and {:?}",
+            name,
+            different_pixels,
+            width * height,
+            reference,
+            actual_path,
+            diff_path
+        );
+    }
+}
+
+#[test]
+fn golden_mandala_closed() {
+    let canvas = render(|canvas| mandala(5, 0.0).draw(0.1, canvas));
+    assert_golden("mandala_closed", &canvas);
+}
+
+#[test]
+fn golden_mandala_open() {
+    let canvas = render(|canvas| mandala(5, 1.0).draw(0.1, canvas));
+    assert_golden("mandala_open", &canvas);
+}
+
+#[test]
+fn golden_mandala_mid_transition() {
+    let mut mandala = mandala(12, 0.0);
+    mandala
+        .set_draw_order(PetalDrawOrder::ByAngle { axis: 270.0 })
+        .set_overlap(PetalOverlap::Flatten {
+            background: COLOR_BACKGROUND,
+        });
+    mandala.start_transition(0.0, 2.0, 1.0);
+    let canvas = render(|canvas| mandala.draw(1.0, canvas));
+    assert_golden("mandala_mid_transition", &canvas);
+}
+
+#[test]
+fn golden_mandala_kaleidoscope_with_layers() {
+    let mut mandala = mandala(8, 1.0);
+    mandala.set_layout(PetalLayout::Kaleidoscope { offset: 10.0 }, 0.0, 0.0);
+    mandala.set_layer(
+        LayerKind::Hub,
+        MandalaLayer::new(
+            "tests/petal.svg",
+            LayerState::new(Color::BLUE, Transform::scale((0.5, 0.5))),
+            LayerState::new(Color::GREEN, Transform::scale((0.2, 0.2))),
+        ),
+    );
+    let canvas = render(|canvas| mandala.draw(1.0, canvas));
+    assert_golden("mandala_kaleidoscope_with_layers", &canvas);
+}
+
+#[test]
+fn golden_concentric_mandala() {
+    let mut concentric =
+        ConcentricMandala::new((SIZE as f32 / 2.0, SIZE as f32 / 2.0), (0.1, 0.1), 1.0);
+    concentric
+        .add_ring(MandalaRing::new(
+            "tests/petal.svg",
+            6,
+            0.0,
+            0.0,
+            mandala_state_open(),
+            mandala_state_closed(),
+        ))
+        .add_ring(MandalaRing::new(
+            "tests/petal.svg",
+            12,
+            200.0,
+            15.0,
+            mandala_state_open(),
+            mandala_state_closed(),
+        ));
+    let canvas = render(|canvas| concentric.draw(0.1, canvas));
+    assert_golden("concentric_mandala", &canvas);
+}
+
+#[test]
+fn golden_presets() {
+    for preset in Preset::ALL.iter() {
+        let mut mandala = preset.build((SIZE as f32 / 2.0, SIZE as f32 / 2.0), 0.25, 0.5);
+        let mut canvas = RgbaCanvas::new(SIZE, SIZE, preset.background());
+        mandala.draw(0.1, &mut canvas);
+        assert_golden(&format!("preset_{}", preset.name()), &canvas);
+    }
+}
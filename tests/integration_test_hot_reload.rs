@@ -0,0 +1,137 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{Color, HotReload, Mandala, MandalaState, MeshBuffer, Transform};
+use std::{env, fs, path::PathBuf};
+
+const TRIANGLE_SVG: &str = r#"<svg><path d="M 0 0 L 10 0 L 0 10 Z"/></svg>"#;
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!(
+        "mandala-hot-reload-{}-{}",
+        std::process::id(),
+        name
+    ))
+}
+
+fn triangles(mandala: &mut Mandala) -> usize {
+    let mut mesh = MeshBuffer::new();
+    mandala.draw(0.1, &mut mesh);
+
+    mesh.triangle_count()
+}
+
+#[test]
+fn integration_test_hot_reload_petal() {
+    let petal = temp_file("petal.svg");
+    let petal_file_name = petal.to_str().unwrap();
+    fs::copy("tests/petal.svg", &petal).unwrap();
+    let mut mandala = Mandala::new(
+        petal_file_name,
+        (500, 500),
+        (2, 2),
+        5,
+        MandalaState::new(
+            Color::RED,
+            Transform::rotate(90),
+            Transform::translate((50.0, 0.0)),
+            Transform::scale((1.0, 1.0)),
+        ),
+        MandalaState::new(
+            Color::YELLOW,
+            Transform::rotate(0.0),
+            Transform::translate((0.0, 0.0)),
+            Transform::scale((0.1, 1.0)),
+        ),
+        0.5,
+    );
+    let mut hot_reload = HotReload::new();
+    hot_reload.watch_petal(petal_file_name);
+    assert!(!hot_reload.poll(&mut mandala).unwrap());
+    assert_eq!(5 * 179, triangles(&mut mandala));
+
+    fs::write(&petal, TRIANGLE_SVG).unwrap();
+    assert!(hot_reload.poll(&mut mandala).unwrap());
+    assert_eq!(5, triangles(&mut mandala));
+    assert_eq!(0.5, mandala.current_value(0.1));
+
+    // A broken file is reported and the previous shape is kept
+    fs::write(&petal, "<svg><path/></svg>").unwrap();
+    assert!(hot_reload.poll(&mut mandala).is_err());
+    assert_eq!(5, triangles(&mut mandala));
+
+    fs::remove_file(&petal).unwrap();
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn integration_test_hot_reload_config() {
+    use mandala_quicksilver::MandalaConfig;
+
+    let config_file = temp_file("mandala.toml");
+    let config_file_name = config_file.to_str().unwrap();
+    fs::write(
+        &config_file,
+        "petal = \"tests/petal.svg\"\npetal_count = 5\n",
+    )
+    .unwrap();
+    let config = MandalaConfig::load(config_file_name).unwrap();
+    let mut mandala = config.build().unwrap();
+    mandala.start_transition(0.0, 2.0, 0.0);
+    let mut hot_reload = HotReload::new();
+    hot_reload.watch_config(config_file_name, &config);
+
+    fs::write(
+        &config_file,
+        "petal = \"tests/petal.svg\"\npetal_count = 12\n",
+    )
+    .unwrap();
+    assert!(hot_reload.poll(&mut mandala).unwrap());
+    assert_eq!(12, mandala.petal_count());
+    assert_eq!(0.5, mandala.current_value(1.0)); // Still half way from 1.0 to 0.0
+
+    fs::write(&config_file, "petal_count = \"seven\"\n").unwrap();
+    assert!(hot_reload.poll(&mut mandala).is_err());
+    assert_eq!(12, mandala.petal_count());
+
+    fs::remove_file(&config_file).unwrap();
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn integration_test_hot_reload_config_keeps_runtime_state() {
+    use mandala_quicksilver::{MandalaConfig, MandalaMetrics};
+    use std::sync::{Arc, Mutex};
+
+    let config_file = temp_file("paused.toml");
+    let config_file_name = config_file.to_str().unwrap();
+    fs::write(&config_file, "petal = \"tests/petal.svg\"\n").unwrap();
+    let config = MandalaConfig::load(config_file_name).unwrap();
+    let mut mandala = config.build().unwrap();
+    let metrics = Arc::new(Mutex::new(MandalaMetrics::new()));
+    mandala.set_observer(metrics.clone());
+    mandala.freeze_at(0.0, 0.25).unwrap();
+    mandala.pause(1.0);
+    let mut hot_reload = HotReload::new();
+    hot_reload.watch_config(config_file_name, &config);
+
+    fs::write(
+        &config_file,
+        "petal = \"tests/petal.svg\"\npetal_count = 12\n",
+    )
+    .unwrap();
+    assert!(hot_reload.poll(&mut mandala).unwrap());
+    assert_eq!(12, mandala.petal_count());
+    assert!(mandala.is_paused());
+    assert!(mandala.is_frozen());
+    mandala.start_transition(2.0, 1.0, 1.0);
+    assert_eq!(0.25, mandala.current_value(5.0));
+
+    // The observer still hears from the rebuilt mandala
+    mandala.unfreeze(5.0).start_transition(5.0, 1.0, 1.0);
+    triangles(&mut mandala);
+    let metrics = *metrics.lock().unwrap();
+    assert_eq!(1, metrics.transitions);
+    assert_eq!(1, metrics.frames);
+
+    fs::remove_file(&config_file).unwrap();
+}
@@ -0,0 +1,96 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{
+    Color, LayerKind, LayerState, Mandala, MandalaLayer, MandalaState, MeshBuffer, RgbaCanvas,
+    Transform,
+};
+
+fn layer() -> MandalaLayer {
+    MandalaLayer::new(
+        "tests/petal.svg",
+        LayerState::new(Color::YELLOW, Transform::scale((2.0, 2.0))),
+        LayerState::new(Color::RED, Transform::scale((1.0, 1.0))),
+    )
+}
+
+#[test]
+fn integration_test_mandala_layers() {
+    let mandala_state_open = MandalaState::new(
+        Color::RED,
+        Transform::rotate(90),
+        Transform::translate((50.0, 0.0)),
+        Transform::scale((1.0, 1.0)),
+    );
+    let mandala_state_closed = MandalaState::new(
+        Color::YELLOW,
+        Transform::rotate(0.0),
+        Transform::translate((0.0, 0.0)),
+        Transform::scale((0.1, 1.0)),
+    );
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        mandala_state_open,
+        mandala_state_closed,
+        0.5,
+    );
+    mandala
+        .set_layer(LayerKind::Background, layer())
+        .set_layer(LayerKind::Hub, layer())
+        .set_layer(LayerKind::Foreground, layer());
+    assert!(mandala.remove_layer(LayerKind::Foreground).is_some());
+    assert!(mandala.layer(LayerKind::Foreground).is_none());
+    assert!(mandala.layer(LayerKind::Hub).is_some());
+
+    let mut mesh = MeshBuffer::new();
+    mandala.draw(0.1, &mut mesh);
+    let expected = 179 * 7; // Five petals plus background and hub
+    assert_eq!(expected, mesh.triangle_count());
+}
+
+/// A layer of one color, half the size of a petal and pointing up from the center
+fn solid_layer(color: Color) -> MandalaLayer {
+    MandalaLayer::new(
+        "tests/petal.svg",
+        LayerState::new(color, Transform::scale((0.5, 0.5))),
+        LayerState::new(color, Transform::scale((0.5, 0.5))),
+    )
+}
+
+#[test]
+fn integration_test_mandala_layer_order() {
+    let state = || {
+        MandalaState::new(
+            Color::RED,
+            Transform::rotate(0.0),
+            Transform::translate((0.0, 0.0)),
+            Transform::scale((1.0, 1.0)),
+        )
+    };
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (100, 100),
+        (0.5, 0.5),
+        5,
+        state(),
+        state(),
+        1.0,
+    );
+    let mut canvas = RgbaCanvas::new(200, 200, Color::BLACK);
+    let near_center = |mandala: &mut Mandala, canvas: &mut RgbaCanvas| {
+        canvas.clear(Color::BLACK);
+        mandala.draw(1.0, canvas);
+        canvas.pixel(100, 96)
+    };
+    assert_eq!(Color::RED, near_center(&mut mandala, &mut canvas));
+
+    // The petals cover the background, and the hub covers the base of the petals
+    mandala.set_layer(LayerKind::Background, solid_layer(Color::GREEN));
+    assert_eq!(Color::RED, near_center(&mut mandala, &mut canvas));
+    mandala.set_layer(LayerKind::Hub, solid_layer(Color::BLUE));
+    assert_eq!(Color::BLUE, near_center(&mut mandala, &mut canvas));
+    mandala.set_layer(LayerKind::Foreground, solid_layer(Color::YELLOW));
+    assert_eq!(Color::YELLOW, near_center(&mut mandala, &mut canvas));
+}
@@ -0,0 +1,51 @@
+extern crate mandala_quicksilver;
+
+mod common;
+
+use mandala_quicksilver::{FrameStats, MandalaMetrics, MandalaObserver, MeshBuffer, Transition};
+use std::sync::{Arc, Mutex};
+
+/// Keeps the target value of every transition started
+#[derive(Default)]
+struct Targets(Vec<f32>);
+
+impl MandalaObserver for Targets {
+    fn transition_started(&mut self, transition: &Transition) {
+        self.0.push(transition.end_value());
+    }
+}
+
+#[test]
+fn integration_test_observer() {
+    let metrics = Arc::new(Mutex::new(MandalaMetrics::new()));
+    let mut mandala = common::test_mandala(0.0);
+    mandala.set_observer(metrics.clone());
+
+    let mut buffer = MeshBuffer::new();
+    for frame in 0..10 {
+        let time = frame as f64 * 0.1;
+        if frame % 5 == 0 {
+            mandala.start_transition(time, 0.5, 1.0 - time as f32);
+        }
+        mandala.start_transition(time, 0.5, f32::NAN); // Rejected, so not reported
+        buffer.clear();
+        mandala.draw(time, &mut buffer);
+    }
+
+    let metrics = *metrics.lock().unwrap();
+    assert_eq!(2, metrics.transitions);
+    assert_eq!(10, metrics.frames);
+    let last_frame: FrameStats = metrics.last_frame.unwrap();
+    assert_eq!(0.9, last_frame.time);
+    assert_eq!(5, last_frame.shapes);
+    assert_eq!(buffer.triangle_count(), last_frame.triangles);
+    assert!(metrics.max_draw_time >= metrics.mean_draw_time());
+
+    // A custom observer replaces the metrics
+    let targets = Arc::new(Mutex::new(Targets::default()));
+    mandala.set_observer(targets.clone());
+    mandala.start_transition(1.0, 0.5, 0.25);
+    assert!(mandala.remove_observer().is_some());
+    mandala.start_transition(2.0, 0.5, 0.75);
+    assert_eq!(vec![0.25], targets.lock().unwrap().0);
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "osc")]
+
+extern crate mandala_quicksilver;
+
+mod common;
+
+use mandala_quicksilver::{
+    osc::IMMEDIATELY, OscArg, OscMessage, OscPacket, OscReceiver, OscRoute, SampleAdapter,
+    SignalSample,
+};
+use std::{
+    net::UdpSocket,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Poll until 'count' samples arrive, or give up after a second
+fn receive(receiver: &mut OscReceiver, time: f64, count: usize) -> Vec<SignalSample> {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let mut samples = Vec::new();
+    while samples.len() < count && Instant::now() < deadline {
+        samples.extend(receiver.poll(time).unwrap());
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    samples
+}
+
+fn band(name: &str, values: &[f32]) -> OscPacket {
+    OscPacket::Message(OscMessage::new(
+        format!("/muse/elements/{}_absolute", name),
+        values.iter().map(|v| OscArg::Float(*v)).collect(),
+    ))
+}
+
+#[test]
+fn integration_test_osc() {
+    let mut receiver = OscReceiver::bind("127.0.0.1:0").unwrap();
+    receiver
+        .route(OscRoute::new("/muse/elements/alpha_absolute", "alpha").with_mean_of_arguments())
+        .route(OscRoute::new("/muse/elements/{beta,gamma}_absolute", "fast").with_range(0.0, 2.0));
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = receiver.local_addr().unwrap();
+
+    assert!(receiver.poll(0.0).unwrap().is_empty());
+
+    sender
+        .send_to(&band("alpha", &[0.2, 0.4, 0.6, 0.8]).to_bytes(), address)
+        .unwrap();
+    let bundle = OscPacket::Bundle {
+        time_tag: IMMEDIATELY,
+        packets: vec![
+            band("beta", &[1.0]),
+            band("delta", &[5.0]), // Not routed
+            band("gamma", &[0.5]),
+        ],
+    };
+    sender.send_to(&bundle.to_bytes(), address).unwrap();
+    sender.send_to(b"not osc", address).unwrap();
+
+    let samples = receive(&mut receiver, 1.5, 3);
+    let summary: Vec<(&str, f64, f32)> = samples
+        .iter()
+        .map(|s| (s.signal.as_str(), s.sample.time, s.sample.value))
+        .collect();
+    assert_eq!(3, summary.len());
+    assert_eq!("alpha", summary[0].0);
+    assert!((summary[0].2 - 0.5).abs() < 1e-6);
+    assert_eq!(("fast", 1.5, 0.5), summary[1]);
+    assert_eq!(("fast", 1.5, 0.25), summary[2]);
+
+    receive(&mut receiver, 1.5, 1); // Give the invalid packet time to arrive
+    assert_eq!(1, receiver.rejected_packets());
+
+    // Drive a mandala from the routed signal
+    let mut mandala = common::test_mandala(0.0);
+    let mut adapter = SampleAdapter::new();
+    for sample in samples.iter().filter(|s| s.signal == "alpha") {
+        adapter.apply(sample.sample, sample.sample.time, &mut mandala);
+    }
+    assert!((mandala.current_value(2.5) - 0.5).abs() < 1e-6);
+}
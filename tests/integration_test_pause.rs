@@ -0,0 +1,66 @@
+extern crate mandala_quicksilver;
+
+mod common;
+
+use mandala_quicksilver::{Sample, SampleAdapter, Session, SessionPlayer};
+
+#[test]
+fn integration_test_pause() {
+    let mut live = common::test_mandala(0.0);
+    let mut adapter = SampleAdapter::new();
+    live.start_recording(0.0);
+
+    // Feedback for 2 sec, a break of 2 sec, a 1 sec hold at a neutral value, then feedback again
+    let mut seen = Vec::new();
+    let mut previous = 0.0;
+    for frame in 0..400 {
+        let time = frame as f64 / 64.0;
+        match frame {
+            128 => {
+                live.pause(time);
+            }
+            256 => {
+                live.resume(time);
+            }
+            288 => live.freeze_at(time, 0.5).unwrap(),
+            352 => {
+                live.unfreeze(time);
+            }
+            _ => (),
+        }
+        if frame % 16 == 0 {
+            adapter.apply(
+                Sample::new(time, (frame % 48) as f32 / 48.0),
+                time,
+                &mut live,
+            );
+        }
+        let value = live.current_value(time);
+        if (129..256).contains(&frame) {
+            assert_eq!(previous, value); // Paused
+        }
+        if (288..352).contains(&frame) {
+            assert_eq!(0.5, value); // Frozen
+        }
+        if frame == 256 || frame == 352 {
+            assert!((value - previous).abs() < 0.05); // No jump
+        }
+        seen.push((time, value));
+        previous = value;
+    }
+
+    let session = live.stop_recording().unwrap();
+    let session = Session::from_bytes(&session.to_bytes()).unwrap();
+    let mut replayed = common::test_mandala(0.0);
+    let mut player = SessionPlayer::new(session);
+    player.seek(&mut replayed, 0.0);
+    for (time, value) in seen.iter().skip(1) {
+        player.advance(&mut replayed, 1.0 / 64.0);
+        assert_eq!(*value, replayed.current_value(*time));
+    }
+
+    // Seeking into the break restores the pause
+    player.seek(&mut replayed, 3.0);
+    assert!(replayed.is_paused());
+    assert_eq!(seen[192].1, replayed.current_value(3.0));
+}
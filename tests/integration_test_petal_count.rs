@@ -0,0 +1,56 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{Color, Mandala, MandalaState, MeshBuffer, Transform};
+
+const TRIANGLES_PER_PETAL: usize = 179;
+
+fn triangle_count(mandala: &mut Mandala, current_time: f64) -> usize {
+    let mut mesh = MeshBuffer::new();
+    mandala.draw(current_time, &mut mesh);
+
+    mesh.triangle_count()
+}
+
+#[test]
+fn integration_test_petal_count() {
+    let mandala_state_open = MandalaState::new(
+        Color::RED,
+        Transform::rotate(90),
+        Transform::translate((50.0, 0.0)),
+        Transform::scale((1.0, 1.0)),
+    );
+    let mandala_state_closed = MandalaState::new(
+        Color::YELLOW,
+        Transform::rotate(0.0),
+        Transform::translate((0.0, 0.0)),
+        Transform::scale((0.1, 1.0)),
+    );
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        mandala_state_open,
+        mandala_state_closed,
+        1.0,
+    );
+    assert_eq!(5 * TRIANGLES_PER_PETAL, triangle_count(&mut mandala, 0.0));
+
+    // New petals start invisible and grow in
+    mandala.set_petal_count(8, 1.0, 2.0);
+    assert_eq!(8, mandala.petal_count());
+    assert_eq!(5 * TRIANGLES_PER_PETAL, triangle_count(&mut mandala, 1.0));
+    assert_eq!(8 * TRIANGLES_PER_PETAL, triangle_count(&mut mandala, 2.0));
+
+    // Removed petals shrink out while the change is in progress, then are dropped
+    mandala.set_petal_count(3, 4.0, 2.0);
+    assert_eq!(3, mandala.petal_count());
+    assert_eq!(8 * TRIANGLES_PER_PETAL, triangle_count(&mut mandala, 5.0));
+    assert_eq!(3 * TRIANGLES_PER_PETAL, triangle_count(&mut mandala, 6.0));
+
+    // Interrupting a change part way continues from the in-between layout
+    mandala.set_petal_count(6, 7.0, 2.0);
+    mandala.set_petal_count(4, 8.0, 2.0);
+    assert_eq!(6 * TRIANGLES_PER_PETAL, triangle_count(&mut mandala, 9.0));
+    assert_eq!(4 * TRIANGLES_PER_PETAL, triangle_count(&mut mandala, 10.0));
+}
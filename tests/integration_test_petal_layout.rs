@@ -0,0 +1,88 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{
+    Color, Mandala, MandalaState, MeshBuffer, PetalDrawOrder, PetalLayout, PetalOverlap,
+    RgbaCanvas, Transform,
+};
+
+#[test]
+fn integration_test_petal_layout() {
+    let mandala_state_open = MandalaState::new(
+        Color::RED,
+        Transform::rotate(90),
+        Transform::translate((50.0, 0.0)),
+        Transform::scale((1.0, 1.0)),
+    );
+    let mandala_state_closed = MandalaState::new(
+        Color::YELLOW,
+        Transform::rotate(0.0),
+        Transform::translate((0.0, 0.0)),
+        Transform::scale((0.1, 1.0)),
+    );
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        mandala_state_open,
+        mandala_state_closed,
+        1.0,
+    );
+    assert_eq!(PetalLayout::Rotational, mandala.layout());
+
+    mandala.set_layout(PetalLayout::Spiral { spacing: 20.0 }, 0.0, 2.0);
+    assert_eq!(PetalLayout::Spiral { spacing: 20.0 }, mandala.layout());
+    mandala.set_petal_count(21, 1.0, 2.0);
+    mandala.set_layout(PetalLayout::Kaleidoscope { offset: 0.3 }, 3.0, 2.0);
+
+    let mut mesh = MeshBuffer::new();
+    mandala.draw(5.0, &mut mesh);
+    let expected = 179 * 21;
+    assert_eq!(expected, mesh.triangle_count());
+}
+
+/// Draw four petals half way through removing the last, which is drawn half faded out
+fn draw_fading_petal(draw_order: PetalDrawOrder) -> RgbaCanvas {
+    let state = || {
+        MandalaState::new(
+            Color::RED,
+            Transform::rotate(0.0),
+            Transform::translate((0.0, 0.0)),
+            Transform::scale((1.0, 1.0)),
+        )
+    };
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (100, 100),
+        (0.5, 0.5),
+        4,
+        state(),
+        state(),
+        1.0,
+    );
+    mandala
+        .set_overlap(PetalOverlap::Flatten {
+            background: Color::BLACK,
+        })
+        .set_draw_order(draw_order);
+    mandala.set_petal_count(3, 0.0, 2.0);
+
+    let mut canvas = RgbaCanvas::new(200, 200, Color::BLACK);
+    mandala.draw(1.0, &mut canvas);
+
+    canvas
+}
+
+#[test]
+fn integration_test_petal_draw_order() {
+    // Where the fading petal at 270 degrees overlaps the next petal, the one drawn last is on top
+    let faded = Color {
+        r: 0.5,
+        ..Color::RED
+    };
+    let on_top = |draw_order| draw_fading_petal(draw_order).pixel(98, 99);
+    assert_eq!(faded, on_top(PetalDrawOrder::Index));
+    assert_eq!(Color::RED, on_top(PetalDrawOrder::Reverse));
+    assert_eq!(faded, on_top(PetalDrawOrder::ByAngle { axis: 270.0 }));
+    assert_eq!(Color::RED, on_top(PetalDrawOrder::ByAngle { axis: 90.0 }));
+}
@@ -0,0 +1,40 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{Color, Mandala, MandalaState, RgbaCanvas, Transform};
+
+#[test]
+fn integration_test_raster() {
+    let mandala_state_open = MandalaState::new(
+        Color::RED,
+        Transform::rotate(90),
+        Transform::translate((50.0, 0.0)),
+        Transform::scale((1.0, 1.0)),
+    );
+    let mandala_state_closed = MandalaState::new(
+        Color::YELLOW,
+        Transform::rotate(0.0),
+        Transform::translate((0.0, 0.0)),
+        Transform::scale((0.1, 1.0)),
+    );
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (100, 100),
+        (0.2, 0.2),
+        5,
+        mandala_state_open,
+        mandala_state_closed,
+        1.0,
+    );
+
+    let mut canvas = RgbaCanvas::new(200, 200, Color::BLACK);
+    mandala.draw(0.1, &mut canvas);
+    assert_eq!(Color::BLACK, canvas.pixel(0, 0));
+    assert!(canvas.pixel(100, 100) != Color::BLACK);
+
+    #[cfg(feature = "png")]
+    {
+        let mut png = Vec::new();
+        canvas.write_png(&mut png).unwrap();
+        assert_eq!(&[137, 80, 78, 71, 13, 10, 26, 10], &png[..8]);
+    }
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "render")]
+
+use std::{env, fs, process::Command};
+
+fn render(args: &[&str]) {
+    let status = Command::new(env!("CARGO_BIN_EXE_mandala-render"))
+        .args(args)
+        .args(["--size", "64", "--fps", "10", "--transition", "0.2"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn integration_test_render() {
+    let directory = env::temp_dir().join(format!("mandala-render-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let timeline = directory.join("values.csv");
+    fs::write(&timeline, "time,value\n0.0,0.0\n0.3,1.0\n").unwrap();
+    let timeline = timeline.to_str().unwrap();
+    let petal = ["--petal", "tests/petal.svg", "--petals", "5"];
+
+    // 0.3 sec of samples and a 0.2 sec transition at 10 FPS
+    let frames = directory.join("frames");
+    render(
+        &[
+            &["--timeline", timeline, "--out", frames.to_str().unwrap()],
+            &petal[..],
+        ]
+        .concat(),
+    );
+    assert_eq!(6, fs::read_dir(&frames).unwrap().count());
+    assert!(frames.join("frame_00005.png").exists());
+
+    let gif = directory.join("session.gif");
+    render(
+        &[
+            &["--timeline", timeline, "--out", gif.to_str().unwrap()],
+            &petal[..],
+        ]
+        .concat(),
+    );
+    assert_eq!(b"GIF89a", &fs::read(&gif).unwrap()[..6]);
+
+    let config = directory.join("mandala.toml");
+    fs::write(
+        &config,
+        "petal = \"tests/petal.svg\"\ncenter = [32, 32]\nscale = [0.1, 0.1]\n",
+    )
+    .unwrap();
+    let configured = directory.join("configured");
+    render(&[
+        "--timeline",
+        timeline,
+        "--out",
+        configured.to_str().unwrap(),
+        "--config",
+        config.to_str().unwrap(),
+    ]);
+    assert_eq!(6, fs::read_dir(&configured).unwrap().count());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn integration_test_render_rejects_invalid_numbers() {
+    for (flag, value) in &[
+        ("--fps", "inf"),
+        ("--fps", "NaN"),
+        ("--transition", "-1"),
+        ("--transition", "inf"),
+    ] {
+        let status = Command::new(env!("CARGO_BIN_EXE_mandala-render"))
+            .args(["--timeline", "values.csv", "--out", "frames", *flag, *value])
+            .status()
+            .unwrap();
+        assert_eq!(Some(2), status.code(), "{} {}", flag, value);
+    }
+}
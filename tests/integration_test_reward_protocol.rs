@@ -0,0 +1,65 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{
+    Color, Mandala, MandalaState, RewardProtocol, Sample, SampleAdapter, Transform,
+};
+
+#[test]
+fn integration_test_reward_protocol() {
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        MandalaState::new(
+            Color::RED,
+            Transform::rotate(90),
+            Transform::translate((50.0, 0.0)),
+            Transform::scale((1.0, 1.0)),
+        ),
+        MandalaState::new(
+            Color::YELLOW,
+            Transform::rotate(0.0),
+            Transform::translate((0.0, 0.0)),
+            Transform::scale((0.1, 1.0)),
+        ),
+        0.0,
+    );
+    let mut protocol = RewardProtocol::new()
+        .with_baseline(10.0, 0.8)
+        .with_hold_time(1.0);
+    let mut adapter = SampleAdapter::new();
+
+    // 10 sec of resting alpha, 4 sec of high alpha, then resting again
+    let mut successes = 0;
+    for i in 0..200 {
+        let time = i as f64 * 0.1;
+        let resting = 1.0 + (i as f32 * 1.7).sin() * 0.2;
+        let alpha = if (100..140).contains(&i) {
+            resting + 1.0
+        } else {
+            resting
+        };
+        let reward = protocol.apply(Sample::new(time, alpha), time, &mut adapter, &mut mandala);
+        if reward.success {
+            successes += 1;
+        }
+        if i == 101 {
+            assert_eq!(1.0, reward.value);
+            assert!(mandala.current_value(time + 0.2) > 0.9);
+        }
+        if i == 199 {
+            assert_eq!(0.0, reward.value); // The threshold has risen with the high alpha
+        }
+    }
+    assert!(mandala.current_value(20.0) < 0.1);
+
+    let stats = protocol.stats();
+    assert_eq!(200, stats.samples);
+    assert_eq!(successes, stats.success_times.len());
+    assert_eq!(2, successes); // Rewards stop once the threshold catches up after 2 sec
+    assert!(stats.success_times.iter().all(|t| (10.0..12.5).contains(t)));
+    // Resting noise beats the 80th percentile about a fifth of the time, but never for long enough to succeed
+    assert!(stats.rewarded_fraction() > 0.15 && stats.rewarded_fraction() < 0.35);
+    assert!(stats.mean_threshold.unwrap() > stats.mean_metric);
+}
@@ -0,0 +1,43 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{
+    Color, Mandala, MandalaState, Sample, SampleAdapter, Smoothing, Transform,
+};
+
+#[test]
+fn integration_test_sample_adapter() {
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        MandalaState::new(
+            Color::RED,
+            Transform::rotate(90),
+            Transform::translate((50.0, 0.0)),
+            Transform::scale((1.0, 1.0)),
+        ),
+        MandalaState::new(
+            Color::YELLOW,
+            Transform::rotate(0.0),
+            Transform::translate((0.0, 0.0)),
+            Transform::scale((0.1, 1.0)),
+        ),
+        0.0,
+    );
+    let mut adapter = SampleAdapter::new()
+        .with_smoothing(Smoothing::Median { window: 3 })
+        .with_outlier_rejection(4.0);
+
+    // Samples every 0.5 sec, with a spike which should not reach the mandala
+    let values = [0.2, 0.2, 0.2, 0.2, 1.0, 0.2];
+    for (i, value) in values.iter().enumerate() {
+        let time = i as f64 * 0.5;
+        adapter.apply(Sample::new(time, *value), time, &mut mandala);
+        if i >= 2 {
+            assert!((mandala.current_value(time + 0.4) - 0.2).abs() < 1e-6);
+        }
+    }
+    assert_eq!(0.5, adapter.interval().unwrap());
+    assert_eq!(0.5, adapter.transition_duration());
+}
@@ -0,0 +1,112 @@
+extern crate mandala_quicksilver;
+
+mod common;
+
+use mandala_quicksilver::{
+    Easing, Mandala, Sample, SampleAdapter, Session, SessionPlayer, SignalSample,
+};
+use std::{env, fs};
+
+fn session_file(name: &str) -> String {
+    env::temp_dir()
+        .join(format!("mandala-session-{}-{}", std::process::id(), name))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Record a live session at 64 frames per second, so frame times add up exactly, returning the value seen at each frame
+fn record(mandala: &mut Mandala) -> Vec<(f64, f32)> {
+    let mut adapter = SampleAdapter::new();
+    let mut seen = Vec::new();
+    mandala.start_recording(0.0);
+    mandala.set_easing(Easing::EaseInOut);
+    for frame in 0..300 {
+        let time = frame as f64 / 64.0;
+        if frame % 13 == 0 {
+            let signal = SignalSample::new(
+                "alpha",
+                Sample::new(time, (frame as f32 * 0.37).sin().abs()),
+            );
+            mandala.record_signal(&signal);
+            adapter.apply(signal.sample, time, mandala);
+        }
+        seen.push((time, mandala.current_value(time)));
+    }
+
+    seen
+}
+
+#[test]
+fn integration_test_session_replay() {
+    let mut live = common::test_mandala(0.5);
+    let seen = record(&mut live);
+    let session = live.stop_recording().unwrap();
+    assert!(live.recording().is_none());
+    assert_eq!(2 * 24, session.events().len()); // A signal and a transition every 13 frames
+
+    let csv_file = session_file("session.csv");
+    let binary_file = session_file("session.bin");
+    session.save(&csv_file).unwrap();
+    session.save(&binary_file).unwrap();
+    let from_csv = Session::load(&csv_file).unwrap();
+    let from_binary = Session::load(&binary_file).unwrap();
+    assert!(fs::metadata(&binary_file).unwrap().len() < fs::metadata(&csv_file).unwrap().len());
+    fs::remove_file(&csv_file).unwrap();
+    fs::remove_file(&binary_file).unwrap();
+    assert_eq!(session, from_csv);
+    assert_eq!(session, from_binary);
+
+    // Replay at normal speed into a mandala in a different state reproduces every frame exactly
+    let mut replayed = common::test_mandala(0.5);
+    replayed.set_value(0.0);
+    let mut player = SessionPlayer::new(from_csv);
+    let mut signals = 0;
+    for (frame, (time, value)) in seen.iter().enumerate() {
+        let elapsed = if frame == 0 {
+            0.0
+        } else {
+            time - seen[frame - 1].0
+        };
+        signals += player.advance(&mut replayed, elapsed).len();
+        assert_eq!(*time, player.position());
+        assert_eq!(*value, replayed.current_value(*time));
+    }
+    assert_eq!(24, signals);
+    assert!(player.is_finished());
+    assert_eq!(Easing::default(), replayed.easing()); // Recorded easings are not kept after replay
+
+    // Seeking back and forward gives the same values as playing through
+    let mut player = SessionPlayer::new(from_binary).with_speed(4.0);
+    let mut sought = common::test_mandala(0.5);
+    for frame in &[250, 20, 133, 0, 299] {
+        let (time, value) = seen[*frame];
+        player.seek(&mut sought, time);
+        assert_eq!(value, sought.current_value(time));
+    }
+
+    // Speed up plays 4 session seconds per real second
+    player.seek(&mut sought, 0.0);
+    player.advance(&mut sought, 1.0);
+    assert_eq!(4.0, player.position());
+    assert_eq!(seen[256].1, sought.current_value(seen[256].0));
+}
+
+#[test]
+fn integration_test_session_does_not_replay_untimed_changes() {
+    let mut live = common::test_mandala(0.5);
+    live.start_recording(0.0);
+    live.start_transition(0.0, 1.0, 0.5);
+    live.set_value(1.0);
+    live.set_petal_count(8, 0.5, 0.0);
+    let session = live.stop_recording().unwrap();
+    assert_eq!(1, session.events().len());
+    assert_eq!((1.0, 8), (live.current_value(2.0), live.petal_count()));
+
+    // The replay follows the recorded transition and keeps its own petals
+    let mut replayed = common::test_mandala(0.5);
+    SessionPlayer::new(session).seek(&mut replayed, 2.0);
+    assert_eq!(
+        (0.5, 5),
+        (replayed.current_value(2.0), replayed.petal_count())
+    );
+}
@@ -0,0 +1,122 @@
+extern crate mandala_quicksilver;
+
+mod common;
+
+use mandala_quicksilver::{
+    ChannelSource, MultiSample, SampleAdapter, SignalSource, SourceError, StreamInfo,
+    TcpLineSource, UdpLineSource,
+};
+use std::{
+    io::Write,
+    net::{TcpListener, UdpSocket},
+    thread,
+    time::{Duration, Instant},
+};
+
+fn eeg_info() -> StreamInfo {
+    StreamInfo::new("eeg", &["alpha", "beta"], Some(10.0))
+}
+
+/// Pull until 'count' samples arrive, or give up after a second
+fn pull(source: &mut dyn SignalSource, count: usize) -> Vec<MultiSample> {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let mut samples = Vec::new();
+    while samples.len() < count && Instant::now() < deadline {
+        samples.extend(source.pull().unwrap());
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    samples
+}
+
+#[test]
+fn integration_test_udp_line_source() {
+    let mut source = UdpLineSource::bind("127.0.0.1:0", eeg_info()).unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = source.local_addr().unwrap();
+
+    sender
+        .send_to(b"# time,alpha,beta\n0.0,0.1,0.2\n0.1,0.3,0.4\n", address)
+        .unwrap();
+    sender.send_to(b"0.2,0.5", address).unwrap(); // Missing a channel
+    sender.send_to(b"0.3,0.7,0.8", address).unwrap();
+
+    let samples = pull(&mut source, 3);
+    assert_eq!(
+        vec![
+            MultiSample::new(0.0, vec![0.1, 0.2]),
+            MultiSample::new(0.1, vec![0.3, 0.4]),
+            MultiSample::new(0.3, vec![0.7, 0.8]),
+        ],
+        samples
+    );
+    assert_eq!(1, source.rejected_lines());
+}
+
+#[test]
+fn integration_test_tcp_line_source() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut source = TcpLineSource::connect(listener.local_addr().unwrap(), eeg_info()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    server.write_all(b"0.0,0.1,0.2\n0.1,0.3").unwrap(); // Ends part way through a line
+    server.flush().unwrap();
+    assert_eq!(
+        vec![MultiSample::new(0.0, vec![0.1, 0.2])],
+        pull(&mut source, 1)
+    );
+
+    server.write_all(b",0.4\n0.2,0.5,0.6").unwrap();
+    drop(server); // The last line is complete when the connection closes
+    assert_eq!(
+        vec![
+            MultiSample::new(0.1, vec![0.3, 0.4]),
+            MultiSample::new(0.2, vec![0.5, 0.6]),
+        ],
+        pull(&mut source, 2)
+    );
+    assert!(matches!(source.pull(), Err(SourceError::Disconnected)));
+}
+
+#[test]
+fn integration_test_tcp_line_source_drops_overlong_lines() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut source = TcpLineSource::connect(listener.local_addr().unwrap(), eeg_info()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    server.write_all(&[b'1'; 70_000]).unwrap(); // No newline
+    server.flush().unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert!(source.pull().unwrap().is_empty());
+
+    server.write_all(&[b'2'; 70_000]).unwrap();
+    server.write_all(b"\n0.0,0.1,0.2\n").unwrap();
+    server.flush().unwrap();
+    assert_eq!(
+        vec![MultiSample::new(0.0, vec![0.1, 0.2])],
+        pull(&mut source, 1)
+    );
+    assert_eq!(1, source.rejected_lines());
+}
+
+#[test]
+fn integration_test_fake_source_drives_mandala() {
+    let mut mandala = common::test_mandala(0.0);
+    let (sender, mut source) = ChannelSource::new(eeg_info());
+    let fake = thread::spawn(move || {
+        for i in 0..5 {
+            let time = 5000.0 + i as f64 * 0.1; // The sender's own clock
+            sender
+                .send(MultiSample::new(time, vec![i as f32 * 0.2, 0.0]))
+                .unwrap();
+        }
+    });
+    fake.join().unwrap();
+
+    let mut adapter = SampleAdapter::new();
+    // Transitions start on the render clock, whatever the sender's clock reads
+    for sample in source.pull_channel("alpha").unwrap() {
+        adapter.apply(sample, 1.0, &mut mandala);
+    }
+    assert!((mandala.current_value(3.0) - 0.8).abs() < 1e-6);
+}
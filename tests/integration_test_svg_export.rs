@@ -0,0 +1,37 @@
+extern crate mandala_quicksilver;
+
+use mandala_quicksilver::{svg::parse_path_from_svg_str, Color, Mandala, MandalaState, Transform};
+
+#[test]
+fn integration_test_svg_export() {
+    let mandala_state_open = MandalaState::new(
+        Color::RED,
+        Transform::rotate(90),
+        Transform::translate((50.0, 0.0)),
+        Transform::scale((1.0, 1.0)),
+    );
+    let mandala_state_closed = MandalaState::new(
+        Color::YELLOW,
+        Transform::rotate(0.0),
+        Transform::translate((0.0, 0.0)),
+        Transform::scale((0.1, 1.0)),
+    );
+    let mut mandala = Mandala::new(
+        "tests/petal.svg",
+        (500, 500),
+        (2, 2),
+        5,
+        mandala_state_open,
+        mandala_state_closed,
+        1.0,
+    );
+
+    let document = mandala.to_svg(0.1);
+    assert!(document.starts_with("<svg "));
+    assert_eq!(5, document.matches("<path ").count());
+    assert_eq!(5, document.matches("transform=\"matrix(").count());
+    assert_eq!(5, document.matches("fill=\"#ff0000\"").count());
+
+    // The exported path data can be loaded again as a petal
+    parse_path_from_svg_str(&document);
+}
@@ -1,7 +1,6 @@
 extern crate mandala_quicksilver;
 
-use mandala_quicksilver::{Mandala, MandalaState};
-use quicksilver::{geom::Transform, graphics::Color};
+use mandala_quicksilver::{Color, Mandala, MandalaState, RangePolicy, Transform};
 
 #[test]
 fn test_mandala_transition() {
@@ -27,6 +26,7 @@ fn test_mandala_transition() {
         mandala_state_closed,
         initial_value,
     );
+    mandala.set_range_policy(RangePolicy::Extrapolate); // Keep values beyond fully open
     let mut current_time = 4.0;
     let transition_duration = 4.0;
     let target_value = 5.0; //+4.0 from initial open value
@@ -0,0 +1,84 @@
+#![cfg(all(
+    feature = "websocket",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+
+extern crate mandala_quicksilver;
+
+mod common;
+
+use mandala_quicksilver::{
+    websocket::to_message, FeedError, Sample, SampleAdapter, SignalSample, WebSocketFeed,
+};
+use std::{
+    net::TcpListener,
+    thread,
+    time::{Duration, Instant},
+};
+use tungstenite::Message;
+
+/// Poll until 'count' values arrive, or give up after a second
+fn receive(feed: &mut WebSocketFeed, count: usize) -> Vec<SignalSample> {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let mut samples = Vec::new();
+    while samples.len() < count && Instant::now() < deadline {
+        samples.extend(feed.poll().unwrap());
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    samples
+}
+
+#[test]
+fn integration_test_websocket() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}/values", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        let values = [0.1, 0.4, 0.7];
+        for (i, value) in values.iter().enumerate() {
+            let sample = SignalSample::new("focus", Sample::new(i as f64 * 0.5, *value));
+            socket
+                .write_message(Message::Text(to_message(&sample)))
+                .unwrap();
+        }
+        socket
+            .write_message(Message::Text("not json".to_string()))
+            .unwrap();
+        socket
+            .write_message(Message::Text(
+                r#"[{"t":1.5,"signal":"calm","value":0.2},{"t":1.5,"signal":"focus","value":0.9}]"#
+                    .to_string(),
+            ))
+            .unwrap();
+        socket.close(None).unwrap();
+        while socket.read_message().is_ok() {} // Wait for the client to acknowledge the close
+    });
+
+    let mut feed = WebSocketFeed::connect(&url).unwrap();
+    let samples = receive(&mut feed, 5);
+    assert_eq!(5, samples.len());
+    assert_eq!(1, feed.rejected_messages());
+    assert_eq!(SignalSample::new("calm", Sample::new(1.5, 0.2)), samples[3]);
+
+    // The connection closes after the last message
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let closed = loop {
+        match feed.poll() {
+            Err(FeedError::Disconnected) => break true,
+            _ if Instant::now() > deadline => break false,
+            _ => thread::sleep(Duration::from_millis(5)),
+        }
+    };
+    assert!(closed);
+    server.join().unwrap();
+
+    // Drive a mandala from one signal
+    let mut mandala = common::test_mandala(0.0);
+    let mut adapter = SampleAdapter::new();
+    for sample in samples.iter().filter(|s| s.signal == "focus") {
+        adapter.apply(sample.sample, sample.sample.time, &mut mandala);
+    }
+    assert!((mandala.current_value(3.0) - 0.9).abs() < 1e-6);
+}